@@ -0,0 +1,164 @@
+use poise::serenity_prelude as serenity;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use crate::i18n::{lang, tr, tr_args};
+use crate::json_file::JsonFile;
+use crate::structs::{Context, Error};
+
+/// Who may use the bot and what a command asks of them, bot-wide or in a guild.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessRules {
+    pub blocked_users: BTreeSet<u64>,
+    pub blocked_roles: BTreeSet<u64>,
+    /// When any user or role is allowed, everyone else is refused.
+    pub allowed_users: BTreeSet<u64>,
+    pub allowed_roles: BTreeSet<u64>,
+    /// Discord permissions a command needs, by its name. A command's
+    /// requirement also applies to its subcommands.
+    pub commands: BTreeMap<String, u64>,
+}
+
+impl AccessRules {
+    fn is_blocked(&self, user_id: u64, roles: &[u64]) -> bool {
+        self.blocked_users.contains(&user_id) || roles.iter().any(|role| self.blocked_roles.contains(role))
+    }
+
+    fn is_allowed(&self, user_id: u64, roles: &[u64]) -> bool {
+        (self.allowed_users.is_empty() && self.allowed_roles.is_empty())
+            || self.allowed_users.contains(&user_id)
+            || roles.iter().any(|role| self.allowed_roles.contains(role))
+    }
+
+    /// What the command and the commands it is a subcommand of require.
+    fn required(&self, qualified_name: &str) -> serenity::Permissions {
+        self.commands.iter()
+            .filter(|(name, _)| qualified_name == name.as_str() || qualified_name.starts_with(&format!("{} ", name)))
+            .fold(serenity::Permissions::empty(), |required, (_, bits)| required | serenity::Permissions::from_bits_truncate(*bits))
+    }
+}
+
+/// Why a command was refused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Denial {
+    Blocked,
+    NotAllowed,
+    MissingPermissions(serenity::Permissions),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct AccessFile {
+    global: AccessRules,
+    guilds: HashMap<u64, AccessRules>,
+}
+
+/// The bot-wide and guild access rules, written to a JSON file on each change.
+pub struct AccessControl {
+    file: JsonFile<AccessFile>,
+}
+
+impl AccessControl {
+    /// Reads the access file, a missing file means everyone may use every command.
+    pub fn load(path: PathBuf) -> Result<AccessControl, Error> {
+        let file: JsonFile<AccessFile> = JsonFile::load(path)?;
+        info!("Loaded the access rules of {} guilds from {:?}", file.read(|file| file.guilds.len()), file.path());
+        Ok(AccessControl { file })
+    }
+
+    /// The bot-wide rules when no guild is given.
+    pub fn rules(&self, guild_id: Option<serenity::GuildId>) -> AccessRules {
+        self.file.read(|file| match guild_id {
+            Some(guild_id) => file.guilds.get(&guild_id.0).cloned().unwrap_or_default(),
+            None => file.global.clone(),
+        })
+    }
+
+    pub async fn update(&self, guild_id: Option<serenity::GuildId>, change: impl FnOnce(&mut AccessRules)) -> Result<AccessRules, Error> {
+        self.file.update(|file| {
+            let rules = match guild_id {
+                Some(guild_id) => file.guilds.entry(guild_id.0).or_default(),
+                None => &mut file.global,
+            };
+            change(rules);
+            rules.clone()
+        }).await
+    }
+
+    /// Checks the bot-wide rules, then those of the guild the command is used in.
+    fn evaluate(
+        &self,
+        guild_id: Option<serenity::GuildId>,
+        user_id: u64,
+        roles: &[u64],
+        qualified_name: &str,
+        permissions: Option<serenity::Permissions>,
+    ) -> Result<(), Denial> {
+        self.file.read(|file| {
+            let guild = guild_id.and_then(|guild_id| file.guilds.get(&guild_id.0));
+            for rules in std::iter::once(&file.global).chain(guild) {
+                if rules.is_blocked(user_id, roles) {
+                    return Err(Denial::Blocked);
+                }
+                if !rules.is_allowed(user_id, roles) {
+                    return Err(Denial::NotAllowed);
+                }
+                // Permissions only exist in guilds, direct messages cannot meet a requirement
+                let required = rules.required(qualified_name);
+                let missing = required - permissions.unwrap_or_else(serenity::Permissions::empty);
+                if !missing.is_empty() && !permissions.map_or(false, |permissions| permissions.administrator()) {
+                    return Err(Denial::MissingPermissions(missing));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Runs before every command: the bot owners may use everything, everyone
+/// else goes through the access rules and is told when refused.
+pub async fn check(ctx: Context<'_>) -> Result<bool, Error> {
+    if ctx.framework().options().owners.contains(&ctx.author().id) {
+        return Ok(true);
+    }
+
+    let member = match ctx.guild_id() {
+        Some(_) => ctx.author_member().await.map(|member| member.into_owned()),
+        None => None,
+    };
+    let roles: Vec<u64> = member.as_ref().map(|member| member.roles.iter().map(|role| role.0).collect()).unwrap_or_default();
+    let permissions: Option<serenity::Permissions> = match &member {
+        Some(member) => match member.permissions {
+            // Slash commands carry the member's permissions in the channel
+            Some(permissions) => Some(permissions),
+            None => member_permissions(ctx, member).await,
+        },
+        None => None,
+    };
+
+    let qualified_name = &ctx.command().qualified_name;
+    let Err(denial) = ctx.data().0.access.evaluate(ctx.guild_id(), ctx.author().id.0, &roles, qualified_name, permissions) else {
+        return Ok(true);
+    };
+    warn!("{} refused to {}: {:?}", qualified_name, ctx.author().id, denial);
+
+    let lang = lang(ctx);
+    let reply = match denial {
+        Denial::Blocked => tr(lang, "access.blocked"),
+        Denial::NotAllowed => tr(lang, "access.not_allowed"),
+        Denial::MissingPermissions(missing) => tr_args(lang, "access.missing_permissions", &[("permissions", &missing)]),
+    };
+    ctx.send(|builder| builder.content(reply).ephemeral(true)).await?;
+    Ok(false)
+}
+
+/// Asks Discord for the permissions of a member in the channel of a prefix
+/// command, the bot does not keep guilds in its cache.
+async fn member_permissions(ctx: Context<'_>, member: &serenity::Member) -> Option<serenity::Permissions> {
+    let guild = member.guild_id.to_partial_guild(ctx.serenity_context()).await.ok()?;
+    let channel = ctx.channel_id().to_channel(ctx.serenity_context()).await.ok()?.guild()?;
+    guild.user_permissions_in(&channel, member).ok()
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::structs::{Error, Region, Scrape, Store};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub store: Store,
+    pub region: Region,
+    /// The search as typed, normalized so casing and spacing do not matter.
+    pub query: String,
+}
+
+struct Entry {
+    scrape: Arc<Scrape>,
+    fetched_at: DateTime<Utc>,
+    expires: Instant,
+}
+
+/// A store search result, fresh or served from the cache.
+#[derive(Clone, Debug)]
+pub struct CachedScrape {
+    pub scrape: Arc<Scrape>,
+    pub fetched_at: DateTime<Utc>,
+    pub hit: bool,
+}
+
+type Slot = Arc<tokio::sync::Mutex<Option<Entry>>>;
+
+/// Keeps store search results for a while so popular searches are only
+/// scraped once per TTL.
+///
+/// Every key has its own async lock, held while fetching: concurrent
+/// identical searches wait on it and then share the single result.
+pub struct SearchCache {
+    ttl: Duration,
+    slots: Mutex<HashMap<CacheKey, Slot>>,
+}
+
+impl SearchCache {
+    pub fn new(ttl: Duration) -> SearchCache {
+        SearchCache { ttl, slots: Mutex::new(HashMap::new()) }
+    }
+
+    pub async fn get_or_fetch<F, Fut>(&self, key: CacheKey, fetch: F) -> Result<CachedScrape, Error>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Scrape, Error>>,
+    {
+        let slot = {
+            let mut slots = self.slots.lock().unwrap();
+            purge_expired(&mut slots);
+            slots.entry(key.clone()).or_default().clone()
+        };
+
+        let mut entry = slot.lock().await;
+        if let Some(cached) = entry.as_ref() {
+            if cached.expires > Instant::now() {
+                info!("Cache hit for {} {} '{}'", key.store, key.region, key.query);
+                return Ok(CachedScrape { scrape: cached.scrape.clone(), fetched_at: cached.fetched_at, hit: true });
+            }
+        }
+
+        // Failures are not cached, the next search tries the store again
+        let scrape = Arc::new(fetch().await?);
+        let fetched_at = Utc::now();
+        *entry = Some(Entry { scrape: scrape.clone(), fetched_at, expires: Instant::now() + self.ttl });
+
+        Ok(CachedScrape { scrape, fetched_at, hit: false })
+    }
+
+    /// Whether a search would be answered without calling the store: its
+    /// result is still fresh, or a fetch it would wait on is running.
+    pub fn is_fresh(&self, key: &CacheKey) -> bool {
+        let Some(slot) = self.slots.lock().unwrap().get(key).cloned() else {
+            return false;
+        };
+        slot.try_lock().map_or(true, |entry| entry.as_ref().map_or(false, |e| e.expires > Instant::now()))
+    }
+}
+
+/// Drops expired entries nobody is currently fetching or waiting on.
+fn purge_expired(slots: &mut HashMap<CacheKey, Slot>) {
+    let now = Instant::now();
+    slots.retain(|_, slot| {
+        if Arc::strong_count(slot) > 1 {
+            return true;
+        }
+        match slot.try_lock() {
+            Ok(entry) => entry.as_ref().map(|e| e.expires > now).unwrap_or(false),
+            Err(_) => true,
+        }
+    });
+}
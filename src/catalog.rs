@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::funcs::normalize_title;
+use crate::json_file::JsonFile;
+use crate::structs::{Error, Game, GameOpt, GamesVec, Store};
+
+/// One game as known across stores.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CatalogGame {
+    /// Never reused nor changed, watchlists and history refer to games by it.
+    pub id: u64,
+    pub name: String,
+    /// Every normalized spelling the game was searched or listed as.
+    pub aliases: BTreeSet<String>,
+    /// The product of the game in each store, keyed by `Store::key`.
+    pub products: BTreeMap<String, String>,
+}
+
+impl CatalogGame {
+    pub fn is_called(&self, title: &str) -> bool {
+        self.aliases.contains(&normalize_title(title))
+    }
+
+    /// The game in a store's results, by its product first and by any of
+    /// its names otherwise.
+    pub fn find_in(&self, games: &GamesVec, store: Store) -> GameOpt {
+        let product = self.products.get(store.key());
+        let game = games.games.iter()
+            .find(|game| product.map_or(false, |product| product == game.product_key()))
+            .or_else(|| games.games.iter().find(|game| self.is_called(&game.name)))
+            .cloned();
+        GameOpt { game }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CatalogFile {
+    next_id: u64,
+    games: Vec<CatalogGame>,
+}
+
+/// The games matched so far, written to a JSON file as new matches come in.
+pub struct Catalog {
+    file: JsonFile<CatalogFile>,
+}
+
+impl Catalog {
+    /// Reads the catalog file, a missing file means an empty catalog.
+    pub fn load(path: PathBuf) -> Result<Catalog, Error> {
+        let file: JsonFile<CatalogFile> = JsonFile::load(path)?;
+        info!("Loaded {} catalog games from {:?}", file.read(|file| file.games.len()), file.path());
+        Ok(Catalog { file })
+    }
+
+    /// The game a search refers to, whatever store's spelling it uses.
+    pub fn resolve(&self, query: &str) -> Option<CatalogGame> {
+        self.file.read(|file| file.games.iter().find(|game| game.is_called(query)).cloned())
+    }
+
+    pub fn get(&self, id: u64) -> Option<CatalogGame> {
+        self.file.read(|file| file.games.iter().find(|game| game.id == id).cloned())
+    }
+
+    /// Adds what a search matched to the catalog: every store's listing, and
+    /// the query when a listing is called the same, become aliases of one
+    /// game, found by a product or a name it already has, or created otherwise.
+    pub async fn record(&self, name: &str, query: &str, matches: &[(Store, Game)]) -> Result<CatalogGame, Error> {
+        let mut games = self.record_many(&[(name, query, matches)]).await?;
+        Ok(games.remove(0))
+    }
+
+    /// Records many searches as `record` does, writing the file once.
+    pub async fn record_many(&self, searches: &[(&str, &str, &[(Store, Game)])]) -> Result<Vec<CatalogGame>, Error> {
+        self.file.update_if(|file| {
+            let mut games: Vec<CatalogGame> = Vec::new();
+            let mut changed = false;
+            for (name, query, matches) in searches {
+                let (game, game_changed) = add_to(file, name, query, matches);
+                games.push(game);
+                changed |= game_changed;
+            }
+            (games, changed)
+        }).await
+    }
+}
+
+/// Records one search in the file, and whether that changed it.
+fn add_to(file: &mut CatalogFile, name: &str, query: &str, matches: &[(Store, Game)]) -> (CatalogGame, bool) {
+    let known = file.games.iter().position(|game| {
+        game.is_called(name) || matches.iter().any(|(store, found)| {
+            game.products.get(store.key()).map_or(false, |product| product == found.product_key())
+        })
+    });
+    let index = match known {
+        Some(index) => index,
+        None => {
+            let id = file.next_id.max(1);
+            file.next_id = id + 1;
+            info!("New catalog game {}: {}", id, name);
+            file.games.push(CatalogGame { id, name: name.to_string(), ..Default::default() });
+            file.games.len() - 1
+        }
+    };
+
+    let game = &mut file.games[index];
+    let mut changed = known.is_none();
+    // The query only names the game when a listing is called the same, a
+    // search for a series may well have listed one of its sequels first
+    let query = normalize_title(query);
+    let names_game = matches.iter().any(|(_, found)| normalize_title(&found.name) == query);
+    let query = if names_game { query } else { String::new() };
+    for alias in [name, &query].into_iter().chain(matches.iter().map(|(_, found)| found.name.as_str())) {
+        let alias = normalize_title(alias);
+        if !alias.is_empty() {
+            changed |= game.aliases.insert(alias);
+        }
+    }
+    for (store, found) in matches {
+        if !found.product_key().is_empty() {
+            changed |= game.products.insert(store.key().to_string(), found.product_key().to_string()).as_deref() != Some(found.product_key());
+        }
+    }
+    (game.clone(), changed)
+}
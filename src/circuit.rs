@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::structs::Store;
+
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    open_until: Option<DateTime<Utc>>,
+    probing: bool,
+    last_error: Option<String>,
+}
+
+/// Whether a store is being called.
+#[derive(Clone, Debug)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls are skipped until the next probe after repeated failures.
+    Open { failures: u32, probe_at: DateTime<Utc>, last_error: Option<String> },
+}
+
+/// Returned instead of calling a store whose circuit is open.
+#[derive(Debug)]
+pub struct StoreDegraded {
+    pub store: Store,
+    pub probe_at: DateTime<Utc>,
+}
+
+impl Display for StoreDegraded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is degraded, next attempt at {}", self.store, self.probe_at)
+    }
+}
+
+impl std::error::Error for StoreDegraded {}
+
+/// Stops calling a store after `threshold` failures in a row, then lets a
+/// single probe through once `cooldown` has passed: a successful probe
+/// closes the circuit, a failed one keeps it open for another cooldown.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    circuits: Mutex<HashMap<Store, Circuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker { threshold: threshold.max(1), cooldown, circuits: Mutex::new(HashMap::new()) }
+    }
+
+    fn cooldown(&self) -> chrono::Duration {
+        chrono::Duration::from_std(self.cooldown).unwrap_or(chrono::Duration::minutes(5))
+    }
+
+    /// Whether the store may be called right now.
+    pub fn allow(&self, store: Store) -> Result<(), StoreDegraded> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(store).or_default();
+        let now = Utc::now();
+        // A probe that never reported back does not block the next one forever
+        let probe_lost = |until: DateTime<Utc>| until + self.cooldown() <= now;
+        match circuit.open_until {
+            None => Ok(()),
+            Some(until) if until <= now && (!circuit.probing || probe_lost(until)) => {
+                info!("Probing {} after its cool-down", store);
+                circuit.probing = true;
+                Ok(())
+            }
+            Some(until) => Err(StoreDegraded { store, probe_at: until }),
+        }
+    }
+
+    pub fn record_success(&self, store: Store) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(store).or_default();
+        if circuit.open_until.is_some() {
+            info!("{} recovered, closing its circuit", store);
+        }
+        *circuit = Circuit::default();
+    }
+
+    pub fn record_failure(&self, store: Store, error: &str) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(store).or_default();
+        circuit.failures += 1;
+        circuit.last_error = Some(error.to_string());
+        if circuit.probing || circuit.failures >= self.threshold {
+            let until = Utc::now() + self.cooldown();
+            warn!("{} failed {} times in a row, skipping it until {}", store, circuit.failures, until);
+            circuit.open_until = Some(until);
+            circuit.probing = false;
+        }
+    }
+
+    pub fn state(&self, store: Store) -> CircuitState {
+        let circuits = self.circuits.lock().unwrap();
+        match circuits.get(&store) {
+            Some(Circuit { failures, open_until: Some(until), last_error, .. }) => CircuitState::Open {
+                failures: *failures,
+                probe_at: *until,
+                last_error: last_error.clone(),
+            },
+            _ => CircuitState::Closed,
+        }
+    }
+}
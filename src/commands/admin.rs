@@ -0,0 +1,269 @@
+use poise::serenity_prelude as serenity;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::Context;
+use crate::access::AccessRules;
+use crate::i18n::{lang, tr, tr_args, Lang};
+use crate::selectors::SelectorConfig;
+use crate::structs::{Command, CommandResult, Error};
+
+/// Reloads the store selectors from the selectors file
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn reload_selectors(ctx: Context<'_>) -> CommandResult {
+    let data = ctx.data();
+    match SelectorConfig::load(data.0.selectors_path.as_deref()) {
+        Ok(selectors) => {
+            let source = selectors.source.clone();
+            *data.0.selectors.write().unwrap() = Arc::new(selectors);
+            info!("Selectors reloaded from {}", source);
+            ctx.say(tr_args(lang(ctx), "selectors.reloaded", &[("source", &source)])).await?;
+        }
+        Err(e) => {
+            error!("Selectors reload failed: {}", e);
+            ctx.say(tr_args(lang(ctx), "selectors.not_reloaded", &[("problems", &e)])).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Whose access rules an admin command changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum AccessScope {
+    #[name = "This server"]
+    Server,
+    #[name = "Bot-wide"]
+    Bot,
+}
+
+/// Permissions a command can be made to require.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum RequiredPermission {
+    #[name = "Manage Server"]
+    ManageGuild,
+    #[name = "Manage Channels"]
+    ManageChannels,
+    #[name = "Manage Messages"]
+    ManageMessages,
+    #[name = "Moderate Members"]
+    ModerateMembers,
+    #[name = "Administrator"]
+    Administrator,
+}
+
+impl RequiredPermission {
+    fn permissions(&self) -> serenity::Permissions {
+        match self {
+            RequiredPermission::ManageGuild => serenity::Permissions::MANAGE_GUILD,
+            RequiredPermission::ManageChannels => serenity::Permissions::MANAGE_CHANNELS,
+            RequiredPermission::ManageMessages => serenity::Permissions::MANAGE_MESSAGES,
+            RequiredPermission::ModerateMembers => serenity::Permissions::MODERATE_MEMBERS,
+            RequiredPermission::Administrator => serenity::Permissions::ADMINISTRATOR,
+        }
+    }
+}
+
+/// The guild whose rules change, none for the bot-wide ones.
+fn scope_guild(ctx: Context<'_>, scope: Option<AccessScope>) -> Result<Option<serenity::GuildId>, Error> {
+    match (scope, ctx.guild_id()) {
+        (Some(AccessScope::Bot), _) => Ok(None),
+        (_, Some(guild_id)) => Ok(Some(guild_id)),
+        (Some(AccessScope::Server), None) => Err(tr(lang(ctx), "access.no_server").into()),
+        (None, None) => Ok(None),
+    }
+}
+
+fn scope_name(lang: Lang, guild_id: Option<serenity::GuildId>) -> String {
+    match guild_id {
+        Some(guild_id) => tr_args(lang, "access.scope_server", &[("guild", &guild_id)]),
+        None => tr(lang, "access.scope_bot"),
+    }
+}
+
+/// Manages who may use the bot and what commands require
+#[poise::command(
+    prefix_command,
+    slash_command,
+    owners_only,
+    subcommands("show", "block", "unblock", "allow", "unallow", "require"),
+)]
+pub async fn access(_ctx: Context<'_>) -> CommandResult {
+    Ok(())
+}
+
+/// Shows the access rules
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn show(
+    ctx: Context<'_>,
+    #[description = "Bot-wide or this server's rules, this server's by default"] scope: Option<AccessScope>,
+) -> CommandResult {
+    let guild_id = scope_guild(ctx, scope)?;
+    let lang: Lang = lang(ctx);
+    let rules: AccessRules = ctx.data().0.access.rules(guild_id);
+
+    let mentions = |ids: &BTreeSet<u64>, mention: &str| {
+        if ids.is_empty() { tr(lang, "access.none") } else { ids.iter().map(|id| format!("<{}{}>", mention, id)).collect::<Vec<_>>().join(", ") }
+    };
+    let commands = if rules.commands.is_empty() {
+        tr(lang, "access.none")
+    } else {
+        rules.commands.iter()
+            .map(|(name, bits)| format!("`{}`: {}", name, serenity::Permissions::from_bits_truncate(*bits)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    ctx.send(|builder| {
+        builder.embed(|e| {
+            e.title(tr_args(lang, "access.title", &[("scope", &scope_name(lang, guild_id))]))
+            .field(tr(lang, "access.blocked_users"), mentions(&rules.blocked_users, "@"), false)
+            .field(tr(lang, "access.blocked_roles"), mentions(&rules.blocked_roles, "@&"), false)
+            .field(tr(lang, "access.allowed_users"), mentions(&rules.allowed_users, "@"), false)
+            .field(tr(lang, "access.allowed_roles"), mentions(&rules.allowed_roles, "@&"), false)
+            .field(tr(lang, "access.requirements"), commands, false)
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Blocks a user or a role from using the bot
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn block(
+    ctx: Context<'_>,
+    #[description = "User to block"] user: Option<serenity::User>,
+    #[description = "Role to block"] role: Option<serenity::Role>,
+    #[description = "Bot-wide or in this server, this server by default"] scope: Option<AccessScope>,
+) -> CommandResult {
+    change_list(ctx, scope, user, role, "blocked", |rules| (&mut rules.blocked_users, &mut rules.blocked_roles), true).await
+}
+
+/// Lifts the block of a user or a role
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn unblock(
+    ctx: Context<'_>,
+    #[description = "User to unblock"] user: Option<serenity::User>,
+    #[description = "Role to unblock"] role: Option<serenity::Role>,
+    #[description = "Bot-wide or in this server, this server by default"] scope: Option<AccessScope>,
+) -> CommandResult {
+    change_list(ctx, scope, user, role, "blocked", |rules| (&mut rules.blocked_users, &mut rules.blocked_roles), false).await
+}
+
+/// Allows a user or a role, once anyone is allowed everyone else is refused
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn allow(
+    ctx: Context<'_>,
+    #[description = "User to allow"] user: Option<serenity::User>,
+    #[description = "Role to allow"] role: Option<serenity::Role>,
+    #[description = "Bot-wide or in this server, this server by default"] scope: Option<AccessScope>,
+) -> CommandResult {
+    change_list(ctx, scope, user, role, "allowed", |rules| (&mut rules.allowed_users, &mut rules.allowed_roles), true).await
+}
+
+/// Takes a user or a role off the allowlist, an empty allowlist allows everyone
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn unallow(
+    ctx: Context<'_>,
+    #[description = "User to take off the allowlist"] user: Option<serenity::User>,
+    #[description = "Role to take off the allowlist"] role: Option<serenity::Role>,
+    #[description = "Bot-wide or in this server, this server by default"] scope: Option<AccessScope>,
+) -> CommandResult {
+    change_list(ctx, scope, user, role, "allowed", |rules| (&mut rules.allowed_users, &mut rules.allowed_roles), false).await
+}
+
+/// Makes a command require a permission
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn require(
+    ctx: Context<'_>,
+    #[description = "Command, as in `deal` or `watch add`"]
+    #[autocomplete = "autocomplete_command"]
+    command: String,
+    #[description = "Permission the command requires, or none to drop the requirement"] permission: Option<RequiredPermission>,
+    #[description = "Bot-wide or in this server, this server by default"] scope: Option<AccessScope>,
+) -> CommandResult {
+    let guild_id = scope_guild(ctx, scope)?;
+    let lang: Lang = lang(ctx);
+    let command = command.trim().trim_start_matches('/').to_string();
+    if !command_names(ctx).contains(&command) {
+        ctx.say(tr_args(lang, "access.unknown_command", &[("command", &command)])).await?;
+        return Ok(());
+    }
+
+    ctx.data().0.access.update(guild_id, |rules| match permission {
+        Some(permission) => {
+            rules.commands.insert(command.clone(), permission.permissions().bits());
+        }
+        None => {
+            rules.commands.remove(&command);
+        }
+    }).await?;
+    info!("Requirement of {} set to {:?} ({:?})", command, permission, guild_id);
+
+    let scope = scope_name(lang, guild_id);
+    let reply = match permission {
+        Some(permission) => tr_args(lang, "access.required", &[("command", &command), ("permission", &permission.permissions()), ("scope", &scope)]),
+        None => tr_args(lang, "access.not_required", &[("command", &command), ("scope", &scope)]),
+    };
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+/// Adds the user and the role to one of the lists, or takes them off it.
+async fn change_list(
+    ctx: Context<'_>,
+    scope: Option<AccessScope>,
+    user: Option<serenity::User>,
+    role: Option<serenity::Role>,
+    list: &str,
+    lists: impl FnOnce(&mut AccessRules) -> (&mut BTreeSet<u64>, &mut BTreeSet<u64>),
+    add: bool,
+) -> CommandResult {
+    let guild_id = scope_guild(ctx, scope)?;
+    let lang: Lang = lang(ctx);
+    if user.is_none() && role.is_none() {
+        ctx.say(tr(lang, "access.no_target")).await?;
+        return Ok(());
+    }
+
+    ctx.data().0.access.update(guild_id, |rules| {
+        let (users, roles) = lists(rules);
+        for (ids, id) in [(users, user.as_ref().map(|user| user.id.0)), (roles, role.as_ref().map(|role| role.id.0))] {
+            if let Some(id) = id {
+                if add { ids.insert(id); } else { ids.remove(&id); }
+            }
+        }
+    }).await?;
+
+    let targets: Vec<String> = user.iter().map(|user| format!("<@{}>", user.id))
+        .chain(role.iter().map(|role| format!("<@&{}>", role.id)))
+        .collect();
+    info!("{} {} {} ({:?})", targets.join(", "), if add { "added to" } else { "removed from" }, list, guild_id);
+    let reply = format!("access.{}_{}", list, if add { "added" } else { "removed" });
+    ctx.say(tr_args(lang, &reply, &[("targets", &targets.join(", ")), ("scope", &scope_name(lang, guild_id))])).await?;
+    Ok(())
+}
+
+/// Every command and subcommand, by the name rules refer to it with.
+fn command_names(ctx: Context<'_>) -> Vec<String> {
+    fn walk(commands: &[Command], names: &mut Vec<String>) {
+        for command in commands {
+            names.push(command.qualified_name.clone());
+            walk(&command.subcommands, names);
+        }
+    }
+    let mut names: Vec<String> = Vec::new();
+    walk(&ctx.framework().options().commands, &mut names);
+    names
+}
+
+async fn autocomplete_command(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    command_names(ctx).into_iter()
+        .filter(|name| name.starts_with(partial))
+        .take(25)
+        .collect()
+}
+
+pub fn commands() -> [Command; 2] {
+    [reload_selectors(), access()]
+}
@@ -0,0 +1,203 @@
+use poise::serenity_prelude as serenity;
+use tracing::info;
+
+use crate::Context;
+use crate::i18n::{lang, tr, tr_args, Lang};
+use crate::settings::GuildSettings;
+use crate::structs::{Command, CommandResult, Region, Store};
+
+/// Changes how the bot works in this server
+#[poise::command(
+    prefix_command,
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("show", "region", "language", "store", "channel", "min_discount", "resellers"),
+)]
+pub async fn config(_ctx: Context<'_>) -> CommandResult {
+    Ok(())
+}
+
+/// Shows the settings of this server
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn show(ctx: Context<'_>) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let lang: Lang = lang(ctx);
+    let settings: GuildSettings = ctx.data().0.settings.get(Some(guild_id));
+
+    let region = match settings.region {
+        Some(region) => region.to_string(),
+        None => tr_args(lang, "config.bot_default", &[("value", &ctx.data().0.region)]),
+    };
+    let language = match settings.language {
+        Some(language) => language.native_name().to_string(),
+        None => tr(lang, "config.member_language"),
+    };
+    let stores: Vec<&str> = Store::ALL.into_iter()
+        .filter(|store| !store.is_reseller() && settings.searches(*store))
+        .map(|store| store.name())
+        .collect();
+    let announcements = match settings.announcement_channel {
+        Some(channel) => tr_args(lang, "config.announcing", &[("channel", &format!("<#{}>", channel)), ("discount", &settings.min_discount)]),
+        None => tr(lang, "config.off"),
+    };
+    let resellers = tr(lang, if settings.resellers { "config.on" } else { "config.off" });
+
+    ctx.send(|builder| {
+        builder.embed(|e| {
+            e.title(tr(lang, "config.title"))
+            .field(tr(lang, "config.region"), region, true)
+            .field(tr(lang, "config.language"), language, true)
+            .field(tr(lang, "config.resellers"), resellers, true)
+            .field(tr(lang, "config.announcements"), announcements, false)
+            .field(tr(lang, "config.stores"), stores.join(", "), false)
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Picks the market prices are searched in
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn region(
+    ctx: Context<'_>,
+    #[description = "Country code of the market, or none for the bot's"]
+    #[autocomplete = "autocomplete_region"]
+    code: Option<String>,
+) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let lang: Lang = lang(ctx);
+    let region: Option<Region> = match code.as_deref().map(Region::from_code) {
+        Some(None) => {
+            ctx.say(tr_args(lang, "config.unknown_region", &[("code", &code.unwrap_or_default())])).await?;
+            return Ok(());
+        }
+        Some(region) => region,
+        None => None,
+    };
+    ctx.data().0.settings.update(guild_id, |settings| settings.region = region).await?;
+    info!("Region of guild {} set to {:?}", guild_id, region);
+
+    let region = region.unwrap_or(ctx.data().0.region);
+    ctx.say(tr_args(lang, "config.region_set", &[("region", &region), ("currency", &region.currency())])).await?;
+    Ok(())
+}
+
+/// Picks the language of members whose Discord language the bot does not speak
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "Language, or none to follow the region"] language: Option<Lang>,
+) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    ctx.data().0.settings.update(guild_id, |settings| settings.language = language).await?;
+    info!("Language of guild {} set to {:?}", guild_id, language);
+
+    // Told in the language just picked, the default is for whoever has no other
+    let reply = match language {
+        Some(language) => tr(language, "config.language_set"),
+        None => tr(lang(ctx), "config.language_reset"),
+    };
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+/// Turns a store on or off in `deal` and `search`
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn store(
+    ctx: Context<'_>,
+    #[description = "Store to turn on or off"]
+    #[autocomplete = "autocomplete_store"]
+    store: String,
+    #[description = "Whether the store is searched"] enabled: bool,
+) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let lang: Lang = lang(ctx);
+    let Some(store) = Store::from_key(&store).or_else(|| Store::ALL.into_iter().find(|s| s.name().eq_ignore_ascii_case(&store))) else {
+        ctx.say(tr_args(lang, "config.unknown_store", &[("store", &store)])).await?;
+        return Ok(());
+    };
+    ctx.data().0.settings.update(guild_id, |settings| {
+        if enabled {
+            settings.disabled_stores.remove(store.key());
+        } else {
+            settings.disabled_stores.insert(store.key().to_string());
+        }
+    }).await?;
+    info!("{} {} for guild {}", store, if enabled { "enabled" } else { "disabled" }, guild_id);
+
+    let reply = if enabled { "config.store_enabled" } else { "config.store_disabled" };
+    ctx.say(tr_args(lang, reply, &[("store", &store)])).await?;
+    Ok(())
+}
+
+/// Picks the channel `deal` shares good deals in
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn channel(
+    ctx: Context<'_>,
+    #[description = "Announcement channel, or none to stop announcing"] channel: Option<serenity::GuildChannel>,
+) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    let channel_id: Option<u64> = channel.map(|channel| channel.id.0);
+    let settings = ctx.data().0.settings.update(guild_id, |settings| settings.announcement_channel = channel_id).await?;
+    info!("Announcement channel of guild {} set to {:?}", guild_id, channel_id);
+
+    let lang: Lang = lang(ctx);
+    let reply = match channel_id {
+        Some(channel_id) => tr_args(lang, "config.channel_set", &[("channel", &format!("<#{}>", channel_id)), ("discount", &settings.min_discount)]),
+        None => tr(lang, "config.channel_reset"),
+    };
+    ctx.say(reply).await?;
+    Ok(())
+}
+
+/// Sets the discount a deal needs to be announced
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD", rename = "min-discount")]
+pub async fn min_discount(
+    ctx: Context<'_>,
+    #[description = "Discount in percent"]
+    #[min = 0]
+    #[max = 100]
+    percent: u8,
+) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    ctx.data().0.settings.update(guild_id, |settings| settings.min_discount = percent).await?;
+    info!("Minimum discount of guild {} set to {}%", guild_id, percent);
+
+    ctx.say(tr_args(lang(ctx), "config.min_discount_set", &[("discount", &percent)])).await?;
+    Ok(())
+}
+
+/// Shows or hides key reseller marketplaces in `deal` for this server
+#[poise::command(prefix_command, slash_command, guild_only, required_permissions = "MANAGE_GUILD")]
+pub async fn resellers(
+    ctx: Context<'_>,
+    #[description = "Whether `deal` lists key resellers"] enabled: bool,
+) -> CommandResult {
+    let Some(guild_id) = ctx.guild_id() else { return Ok(()) };
+    ctx.data().0.settings.update(guild_id, |settings| settings.resellers = enabled).await?;
+    info!("Key resellers {} for guild {}", if enabled { "enabled" } else { "disabled" }, guild_id);
+
+    let reply = if enabled { "resellers.enabled" } else { "resellers.disabled" };
+    ctx.say(tr(lang(ctx), reply)).await?;
+    Ok(())
+}
+
+async fn autocomplete_region(_ctx: Context<'_>, partial: &str) -> Vec<poise::AutocompleteChoice<String>> {
+    Region::ALL.into_iter()
+        .filter(|region| region.country().starts_with(&partial.to_uppercase()))
+        .map(|region| poise::AutocompleteChoice { name: format!("{} ({})", region.country(), region.currency()), value: region.country().to_string() })
+        .collect()
+}
+
+async fn autocomplete_store(_ctx: Context<'_>, partial: &str) -> Vec<poise::AutocompleteChoice<String>> {
+    let partial = partial.to_lowercase();
+    Store::ALL.into_iter()
+        .filter(|store| store.name().to_lowercase().contains(&partial))
+        .map(|store| poise::AutocompleteChoice { name: store.name().to_string(), value: store.key().to_string() })
+        .collect()
+}
+
+pub fn commands() -> [Command; 1] {
+    [config()]
+}
@@ -0,0 +1,23 @@
+use tracing::info;
+
+use crate::Context;
+use crate::i18n::{lang, tr, Lang};
+use crate::structs::{Command, CommandResult};
+
+/// Picks the language the bot replies to you in
+#[poise::command(prefix_command, slash_command)]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "Language, or none to follow your Discord language"] language: Option<Lang>,
+) -> CommandResult {
+    ctx.data().0.user_settings.update(ctx.author().id, |settings| settings.language = language).await?;
+    info!("Language of user {} set to {:?}", ctx.author().id, language);
+
+    let reply = if language.is_some() { "language.user_set" } else { "language.user_reset" };
+    ctx.say(tr(lang(ctx), reply)).await?;
+    Ok(())
+}
+
+pub fn commands() -> [Command; 1] {
+    [language()]
+}
@@ -1,12 +1,27 @@
+pub mod admin;
+pub mod config;
+pub mod language;
 pub mod scraper;
 pub mod ping;
+pub mod search;
+pub mod status;
+pub mod watch;
 
 pub use anyhow::{Error, Result};
 
+use crate::i18n::localize_commands;
 use crate::structs::Command;
 
 pub fn commands() -> Vec<Command> {
-    scraper::commands().into_iter()
+    let mut commands: Vec<Command> = scraper::commands().into_iter()
+        .chain(admin::commands())
+        .chain(config::commands())
+        .chain(language::commands())
         .chain(ping::commands())
-        .collect()
-}
\ No newline at end of file
+        .chain(search::commands())
+        .chain(status::commands())
+        .chain(watch::commands())
+        .collect();
+    localize_commands(&mut commands);
+    commands
+}
@@ -1,12 +1,13 @@
 use crate::Context;
+use crate::i18n::{lang, tr};
 use crate::structs::{Command, CommandResult};
 
 #[poise::command(prefix_command, slash_command)]
 pub async fn ping(ctx: Context<'_>) -> CommandResult {
-    ctx.say("I'm alive!").await?;
+    ctx.say(tr(lang(ctx), "ping.alive")).await?;
     Ok(())
 }
 
 pub fn commands() -> [Command; 1] {
     [ping()]
-}
\ No newline at end of file
+}
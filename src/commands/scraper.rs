@@ -1,157 +1,140 @@
-use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, COOKIE};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
 use itertools::Itertools;
-use lazy_static::lazy_static;
-use reqwest::Client;
+use poise::serenity_prelude as serenity;
 use scraper::{Html, Selector};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tracing::info;
+use tracing::{error, info, warn};
 use url::form_urlencoded::byte_serialize;
 
 use crate::Context;
-use crate::funcs::{get_attr_text, get_currency, get_element_text, make_selector, search_in};
-use crate::structs::{Command, CommandResult, Error, Game, GameOpt, GamesVec};
-
-lazy_static! {
-    static ref STEAM_RESULTS_SELECTOR: Selector = make_selector("div[id='search_resultsRows']");
-    static ref STEAM_GAME_SELECTOR: Selector = make_selector("a.search_result_row");
-    static ref STEAM_GAME_TITLE_SELECTOR: Selector = make_selector("span.title");
-    static ref STEAM_GAME_FULL_PRICE_SELECTOR: Selector = make_selector("div.discount_original_price");
-    static ref STEAM_GAME_DISCOUNTED_PRICE_SELECTOR: Selector = make_selector("div.discount_final_price");
-    static ref STEAM_GAME_DISCOUNT_SELECTOR: Selector = make_selector("div.discount_pct");
-    static ref STEAM_GAME_IMG_URL_SELECTOR: Selector = make_selector("div.search_capsule > img");
-
-    static ref EPIC_RESULTS_SELECTOR: Selector = make_selector("main section ul");
-    static ref EPIC_GAME_SELECTOR: Selector = make_selector("li > div > div > a > div > div");
-    static ref EPIC_GAME_TITLE_SELECTOR: Selector = make_selector("div:nth-child(2) > div:nth-child(2) > div > div");
-    static ref EPIC_GAME_FULL_PRICE_SELECTOR: Selector = make_selector("div:nth-child(2) > div:nth-child(3) > div > div:nth-child(2) > div > div:first-child > span > div");
-    static ref EPIC_GAME_DISCOUNTED_PRICE_SELECTOR: Selector = make_selector("div:nth-child(2) > div:nth-child(3) > div > div:nth-child(2) > div > div:nth-child(2) > span");
-    static ref EPIC_GAME_DISCOUNT_SELECTOR: Selector = make_selector("div:nth-child(2) > div:nth-child(3) > div > div:first-child > span > div");
-    static ref EPIC_GAME_IMG_URL_SELECTOR: Selector = make_selector("div:first-child > div > div > div > div > img");
-
-    static ref NUUVEM_RESULTS_SELECTOR: Selector = make_selector("div.products-items");
-    static ref NUUVEM_GAME_SELECTOR: Selector = make_selector("div.product-card--grid a.product-card--wrapper");
-    static ref NUUVEM_GAME_TITLE_SELECTOR: Selector = make_selector("h3.product-title");
-    static ref NUUVEM_GAME_CURRENCY_SELECTOR: Selector = make_selector("sup.currency-symbol");
-    static ref NUUVEM_GAME_PRICE_INTEGER_SELECTOR: Selector = make_selector("span.integer");
-    static ref NUUVEM_GAME_PRICE_DECIMAL_SELECTOR: Selector = make_selector("span.decimal");
-    static ref NUUVEM_GAME_DISCOUNT_SELECTOR: Selector = make_selector("span.product-price--discount");
-    static ref NUUVEM_GAME_IMG_URL_SELECTOR: Selector = make_selector("div.product-img > img");
-
-    static ref GOG_RESULTS_SELECTOR: Selector = make_selector("div.paginated-products-grid");
-    static ref GOG_GAME_SELECTOR: Selector = make_selector("product-tile > a.product-tile");
-    static ref GOG_GAME_TITLE_SELECTOR: Selector = make_selector("div.product-tile__info > div.product-tile__title > product-title > span");
-    static ref GOG_GAME_FULL_PRICE_SELECTOR: Selector = make_selector("div.product-tile__info > div.product-tile__footer > div.product-tile__price-info > product-price > price-value > span.base-value");
-    static ref GOG_GAME_DISCOUNTED_PRICE_SELECTOR: Selector = make_selector("div.product-tile__info > div.product-tile__footer > div.product-tile__price-info > product-price > price-value > span.final-value");
-    static ref GOG_GAME_DISCOUNT_SELECTOR: Selector = make_selector("div.product-tile__info > div.product-tile__footer > div.product-tile__price-info > product-price > price-discount");
-    static ref GOG_GAME_IMG_URL_SELECTOR: Selector = make_selector("div.product-tile__image-wrapper > store-picture > picture > source:nth-child(2)");
-}
+use crate::cache::{CacheKey, CachedScrape};
+use crate::catalog::CatalogGame;
+use crate::circuit::CircuitState;
+use crate::cooldown;
+use crate::fetcher::Fetcher;
+use crate::funcs::{currency_symbol, discount_pct, edition_of, format_price, get_element_text, normalize_query, parse_discount, parse_end_date, search_in, select_attr, select_currency, select_link, select_text, retail_game_name, split_key_listing};
+use crate::i18n::{lang, tr, tr_args, Lang};
+use crate::selectors::{SelectorConfig, StoreSelectors};
+use crate::settings::GuildSettings;
+use crate::structs::{Bundle, Command, CommandResult, Data, Error, Game, GameOpt, GamesVec, Note, PlatformFilter, Region, ResellerOffer, Scrape, Store};
 
 pub async fn get_game_steam(
-    client: Client,
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
     game: String,
-) -> Result<Vec<Game>, Error> {
+) -> Result<Scrape, Error> {
 	// API endpoint var
     const STEAM_URL: &str = "https://store.steampowered.com/search/?term=";
 
     let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
-    let url: String = format!("{}{}", &STEAM_URL, &game_param_encoded);
+    let url: String = format!("{}{}&cc={}", &STEAM_URL, &game_param_encoded, region.country().to_lowercase());
 
     info!("STEAM_URL call: {:#?}", url);
 
-    let response_str: String = client.get(url)
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // let browser = Browser::default().unwrap();
-    // let response_str = browser.new_tab()?
-    // .navigate_to(&url)?
-    // .wait_for_element_with_custom_timeout("div[id='search_resultsRows']", Duration::from_secs(10))?
-    // .get_content()?;
-    //let document = Html::parse_document(&inner_text_content.to_string());
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
 
     let document = Html::parse_document(&response_str);
     //info!("DOCUMENT: {:#?}", document);
 
     let mut game_list: Vec<Game> = Vec::new(); 
+    let mut containers: usize = 0;
+    let mut rows: usize = 0;
 
-    let main_rows_result = document.select(&STEAM_RESULTS_SELECTOR);
+    // Steam shows the platforms as icons, one per system
+    let platform_icons: [(&Option<Selector>, &str); 3] = [
+        (&selectors.platform_windows, "Windows"),
+        (&selectors.platform_macos, "macOS"),
+        (&selectors.platform_linux, "SteamOS + Linux"),
+    ];
+
+    let main_rows_result = document.select(&selectors.results);
     for main_rows in main_rows_result {
-        let game_rows = main_rows.select(&STEAM_GAME_SELECTOR);
+        containers += 1;
+        let game_rows = main_rows.select(&selectors.row);
         for game_row in game_rows {
-            let game_name = get_element_text(&game_row.select(&STEAM_GAME_TITLE_SELECTOR));
-            let game_full_price = get_element_text(&game_row.select(&STEAM_GAME_FULL_PRICE_SELECTOR));
-            let game_discounted_price = get_element_text(&game_row.select(&STEAM_GAME_DISCOUNTED_PRICE_SELECTOR));
-            let game_discount = get_element_text(&game_row.select(&STEAM_GAME_DISCOUNT_SELECTOR));
-            let game_currency = get_currency(&game_row.select(&STEAM_GAME_DISCOUNTED_PRICE_SELECTOR));
-            let game_img_url = get_attr_text(&mut game_row.select(&STEAM_GAME_IMG_URL_SELECTOR), "src");
+            rows += 1;
+            let game_name = select_text(&game_row, &selectors.title);
+            let game_full_price = select_text(&game_row, &selectors.full_price);
+            let game_discounted_price = select_text(&game_row, &selectors.discounted_price);
+            let game_discount = select_text(&game_row, &selectors.discount);
+            let game_currency = select_currency(&game_row, &selectors.discounted_price);
+            let game_img_url = select_attr(&game_row, &selectors.image, &selectors.image_attr);
+            let game_url = select_link(&game_row, &selectors.link, &selectors.link_attr, &url);
 
             if !game_discounted_price.is_empty() || !game_full_price.is_empty() {
+                let platforms: Vec<String> = platform_icons.iter()
+                    .filter(|(icon, _)| icon.as_ref().map_or(false, |icon| game_row.select(icon).next().is_some()))
+                    .map(|(_, name)| name.to_string())
+                    .collect();
+
                 let game: Game = Game {
                     site: "Steam".to_string(),
+                    product_id: game_row.value().attr("data-ds-appid").unwrap_or_default().to_string(),
+                    platforms,
                     name: game_name,
                     currency: game_currency,
                     full_price: game_full_price,
                     discounted_price: game_discounted_price,
                     discount: if game_discount.is_empty() { "0%".to_string() } else { game_discount },
                     img_url: game_img_url,
+                    url: game_url,
+                    ..Default::default()
                 };
                 game_list.push(game);
             }
         }
     }
     
-    info!("Steam search found: {:#?}", &game_list.len());
+    info!("Steam search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
 
-    Ok(game_list)
+    Ok(Scrape { games: game_list, containers, rows })
 }
 
 pub async fn get_game_epic(
-    client: Client,
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
     game: String,
-) -> Result<Vec<Game>, Error> {
+) -> Result<Scrape, Error> {
 	// API endpoint var
-    const EPIC_URL: &str = "https://store.epicgames.com/pt-BR/browse?q=";
+    const EPIC_URL: &str = "https://store.epicgames.com/";
     const PARAMS: &str = "&sortBy=relevancy&sortDir=DESC&count=40";
 
     let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
-    let url: String = format!("{}{}{}", &EPIC_URL, &game_param_encoded, &PARAMS);
+    let url: String = format!("{}{}/browse?q={}{}", &EPIC_URL, region.locale(), &game_param_encoded, &PARAMS);
 
     info!("EPIC_URL call: {:#?}", url);
 
-    let response_str: String = client.get(url)
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // let browser = Browser::default().unwrap();
-    // let response_str = browser.new_tab()?
-    // .navigate_to(&url)?
-    // .wait_for_element_with_custom_timeout("div[id='search_resultsRows']", Duration::from_secs(10))?
-    // .get_content()?;
-    // let document = Html::parse_document(&response_str.to_string());
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
 
     //info!("document: {:#?}", document);
 
     let document = Html::parse_document(&response_str);
 
     let mut game_list: Vec<Game> = Vec::new(); 
+    let mut containers: usize = 0;
+    let mut rows: usize = 0;
 
-    let main_rows_result = document.select(&EPIC_RESULTS_SELECTOR);
+    let main_rows_result = document.select(&selectors.results);
     //info!("EPIC_RESULTS: {:#?}", &main_rows_result.next().unwrap());
     for main_rows in main_rows_result {
-        let game_rows = main_rows.select(&EPIC_GAME_SELECTOR);
+        containers += 1;
+        let game_rows = main_rows.select(&selectors.row);
         //info!("GAME_ROWS: {:#?}", &game_rows.next().unwrap());
         for game_row in game_rows {
-            let game_name = get_element_text(&game_row.select(&EPIC_GAME_TITLE_SELECTOR));
-            let game_full_price = get_element_text(&game_row.select(&EPIC_GAME_FULL_PRICE_SELECTOR));
-            let game_discounted_price = get_element_text(&game_row.select(&EPIC_GAME_DISCOUNTED_PRICE_SELECTOR));
-            let game_discount = get_element_text(&game_row.select(&EPIC_GAME_DISCOUNT_SELECTOR));
-            let game_currency = get_currency(&game_row.select(&EPIC_GAME_DISCOUNTED_PRICE_SELECTOR));
-            let game_img_url = get_attr_text(&mut game_row.select(&EPIC_GAME_IMG_URL_SELECTOR), "src");
+            rows += 1;
+            let game_name = select_text(&game_row, &selectors.title);
+            let game_full_price = select_text(&game_row, &selectors.full_price);
+            let game_discounted_price = select_text(&game_row, &selectors.discounted_price);
+            let game_discount = select_text(&game_row, &selectors.discount);
+            let game_currency = select_currency(&game_row, &selectors.discounted_price);
+            let game_img_url = select_attr(&game_row, &selectors.image, &selectors.image_attr);
+            let game_url = select_link(&game_row, &selectors.link, &selectors.link_attr, &url);
 
             if !game_discounted_price.is_empty() || !game_full_price.is_empty() {
                 let game: Game = Game {
@@ -162,22 +145,27 @@ pub async fn get_game_epic(
                     discounted_price: game_discounted_price,
                     discount: if game_discount.is_empty() { "0%".to_string() } else { game_discount },
                     img_url: game_img_url,
+                    url: game_url,
+                    ..Default::default()
                 };
                 game_list.push(game);
             }
         }
     }
     
-    info!("Epic Games search found: {:#?}", &game_list.len());
+    info!("Epic Games search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
 
-    Ok(game_list)
+    Ok(Scrape { games: game_list, containers, rows })
 }
 
 pub async fn get_game_nuuvem(
-    client: Client,
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    _region: Region,
     game: String,
-) -> Result<Vec<Game>, Error> {
+) -> Result<Scrape, Error> {
 	// API endpoint var
+    // Nuuvem only sells to Brazil, every region searches the same catalog
     const NUUVEM_URL: &str = "https://www.nuuvem.com/br-pt/catalog/page/1/search/";
 
     let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
@@ -185,36 +173,30 @@ pub async fn get_game_nuuvem(
 
     info!("NUUVEM_URL call: {:#?}", url);
 
-    let response_str: String = client.get(url)
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    // let browser = Browser::default().unwrap();
-    // let response_str = browser.new_tab()?
-    // .navigate_to(&url)?
-    // .wait_for_element_with_custom_timeout("div[id='search_resultsRows']", Duration::from_secs(10))?
-    // .get_content()?;
-    //let document = Html::parse_document(&inner_text_content.to_string());
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
 
     let document = Html::parse_document(&response_str);
     //info!("DOCUMENT: {:#?}", document);
 
     let mut game_list: Vec<Game> = Vec::new(); 
+    let mut containers: usize = 0;
+    let mut rows: usize = 0;
 
-    let main_rows_result = document.select(&NUUVEM_RESULTS_SELECTOR);
+    let main_rows_result = document.select(&selectors.results);
     //info!("main_rows_result: {:#?}", main_rows_result);
     for main_rows in main_rows_result {
+        containers += 1;
         //info!("main_rows: {:#?}", main_rows);
-        let game_rows = main_rows.select(&NUUVEM_GAME_SELECTOR);
+        let game_rows = main_rows.select(&selectors.row);
         for game_row in game_rows {
+            rows += 1;
             //info!("game_row: {:#?}", game_row);
-            let game_name = get_element_text(&game_row.select(&NUUVEM_GAME_TITLE_SELECTOR));
-            let game_price = get_element_text(&game_row.select(&NUUVEM_GAME_PRICE_INTEGER_SELECTOR)) + &get_element_text(&game_row.select(&NUUVEM_GAME_PRICE_DECIMAL_SELECTOR));
-            let game_discount = get_element_text(&game_row.select(&NUUVEM_GAME_DISCOUNT_SELECTOR));
-            let game_currency = get_currency(&game_row.select(&NUUVEM_GAME_CURRENCY_SELECTOR));
-            let game_img_url = get_attr_text(&mut game_row.select(&NUUVEM_GAME_IMG_URL_SELECTOR), "src");
+            let game_name = select_text(&game_row, &selectors.title);
+            let game_price = select_text(&game_row, &selectors.price_integer) + &select_text(&game_row, &selectors.price_decimal);
+            let game_discount = select_text(&game_row, &selectors.discount);
+            let game_currency = select_currency(&game_row, &selectors.currency);
+            let game_img_url = select_attr(&game_row, &selectors.image, &selectors.image_attr);
+            let game_url = select_link(&game_row, &selectors.link, &selectors.link_attr, &url);
 
             if !game_price.is_empty() {
                 let game: Game = Game {
@@ -225,21 +207,25 @@ pub async fn get_game_nuuvem(
                     discounted_price: if game_discount.is_empty() { "R$0".to_string() } else { game_price.clone() },
                     discount: if game_discount.is_empty() { "0%".to_string() } else { game_discount },
                     img_url: game_img_url,
+                    url: game_url,
+                    ..Default::default()
                 };
                 game_list.push(game);
             }
         }
     }
     
-    info!("Nuuvem search found: {:#?}", &game_list.len());
+    info!("Nuuvem search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
 
-    Ok(game_list)
+    Ok(Scrape { games: game_list, containers, rows })
 }
 
 pub async fn get_game_gog(
-    client: Client,
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
     game: String,
-) -> Result<Vec<Game>, Error> {
+) -> Result<Scrape, Error> {
 	// API endpoint var
     const GOG_URL: &str = "https://www.gog.com/en/games?query=";
     const PARAMS: &str = "&order=desc:score";
@@ -251,34 +237,35 @@ pub async fn get_game_gog(
 
     // Set cookies
     let mut headers = HeaderMap::new();
-    headers.insert(COOKIE, HeaderValue::from_str("gog_lc=BR_BRL_en-US").unwrap());
+    let locale_cookie = format!("gog_lc={}_{}_en-US", region.country(), region.currency());
+    headers.insert(COOKIE, HeaderValue::from_str(&locale_cookie).unwrap());
 
-    let response_str: String = client.get(url)
-        .headers(headers)
-        .send()
-        .await?
-        .text()
-        .await?;
+    let response_str: String = fetcher.fetch(&selectors, &url, headers).await?;
 
     let document = Html::parse_document(&response_str);
 
     let mut game_list: Vec<Game> = Vec::new(); 
+    let mut containers: usize = 0;
+    let mut rows: usize = 0;
 
-    let main_rows_result = document.select(&GOG_RESULTS_SELECTOR);
+    let main_rows_result = document.select(&selectors.results);
     for main_rows in main_rows_result {
-        let game_rows = main_rows.select(&GOG_GAME_SELECTOR);
+        containers += 1;
+        let game_rows = main_rows.select(&selectors.row);
         for game_row in game_rows {
-            let title_selector = &game_row.select(&GOG_GAME_TITLE_SELECTOR);
+            rows += 1;
+            let title_selector = &game_row.select(&selectors.title);
             let title_counter = title_selector.clone().count();
             let mut game_name = get_element_text(&title_selector);
             if title_counter > 1 {
                 game_name = get_element_text(&title_selector.clone().dropping(title_counter - 1));
             }
-            let game_full_price = get_element_text(&game_row.select(&GOG_GAME_FULL_PRICE_SELECTOR));
-            let game_discounted_price = get_element_text(&game_row.select(&GOG_GAME_DISCOUNTED_PRICE_SELECTOR));
-            let game_discount = get_element_text(&game_row.select(&GOG_GAME_DISCOUNT_SELECTOR));
-            let game_currency = get_currency(&game_row.select(&GOG_GAME_FULL_PRICE_SELECTOR));
-            let game_img_url = get_attr_text(&mut game_row.select(&GOG_GAME_IMG_URL_SELECTOR), "srcset");
+            let game_full_price = select_text(&game_row, &selectors.full_price);
+            let game_discounted_price = select_text(&game_row, &selectors.discounted_price);
+            let game_discount = select_text(&game_row, &selectors.discount);
+            let game_currency = select_currency(&game_row, &selectors.full_price);
+            let game_img_url = select_attr(&game_row, &selectors.image, &selectors.image_attr);
+            let game_url = select_link(&game_row, &selectors.link, &selectors.link_attr, &url);
 
             if !game_discounted_price.is_empty() || !game_full_price.is_empty() {
                 let game: Game = Game {
@@ -289,6 +276,8 @@ pub async fn get_game_gog(
                     discounted_price: game_discounted_price,
                     discount: if game_discount.is_empty() { "0%".to_string() } else { game_discount },
                     img_url: game_img_url,
+                    url: game_url,
+                    ..Default::default()
                 };
                 game_list.push(game);
             }
@@ -296,81 +285,1147 @@ pub async fn get_game_gog(
     }
     
     info!("GOG games: {:#?}", &game_list);
-    info!("GOG search found: {:#?}", &game_list.len());
+    info!("GOG search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
 
-    Ok(game_list)
+    Ok(Scrape { games: game_list, containers, rows })
 }
 
-#[poise::command(prefix_command, slash_command, reuse_response, track_edits)]
-pub async fn deal(
-    ctx: Context<'_>, 
-    game: String, 
-) -> CommandResult {
-    let start = Instant::now();
-    info!("Commands parameters: {{Game: {:#?}}}", &game);
+pub async fn get_game_gmg(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const GMG_URL: &str = "https://www.greenmangaming.com/search?query=";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}", &GMG_URL, &game_param_encoded);
+
+    info!("GMG_URL call: {:#?}", url);
+
+    let headers = locale_headers(region);
 
-    let sites: Vec<&str> = vec!("Steam", "Epic Games", "Nuuvem", "GOG");
-    let client: Client = ctx.data().0.reqwest.clone();
-    let mut site_games: HashMap<&str, GameOpt> = HashMap::new();
+    let response_str: String = fetcher.fetch(&selectors, &url, headers).await?;
+    let scrape = parse_listing("Green Man Gaming", &selectors, &response_str, &url);
 
-    // Get games option
-    let games_steam: GamesVec = GamesVec {
-        games: get_game_steam(client.clone(), game.clone()).await?,
+    info!("Green Man Gaming search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_humble(
+    fetcher: Fetcher,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const HUMBLE_URL: &str = "https://www.humblebundle.com/store/api/search?sort=bestselling&filter=all&request=1&search=";
+    const HUMBLE_PRODUCT_URL: &str = "https://www.humblebundle.com/store/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}", &HUMBLE_URL, &game_param_encoded);
+
+    info!("HUMBLE_URL call: {:#?}", url);
+
+    let headers = locale_headers(region);
+
+    let response_str: String = fetcher.fetch_api(&url, headers).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+
+    let mut game_list: Vec<Game> = Vec::new();
+    let results = response["results"].as_array();
+    let containers: usize = if results.is_some() { 1 } else { 0 };
+    let rows: usize = results.map(|r| r.len()).unwrap_or(0);
+
+    for item in results.into_iter().flatten() {
+        let game_name = item["human_name"].as_str().unwrap_or_default().to_string();
+        let currency = item["current_price"]["currency"].as_str().unwrap_or_default();
+        let (Some(full_price), Some(current_price)) = (item["full_price"]["amount"].as_f64(), item["current_price"]["amount"].as_f64()) else {
+            continue;
+        };
+
+        let mut notes: Vec<Note> = Vec::new();
+        if let Some(inclusion) = humble_inclusion(item) {
+            notes.push(inclusion);
+        }
+
+        let game: Game = Game {
+            site: "Humble Store".to_string(),
+            product_id: item["machine_name"].as_str().unwrap_or_default().to_string(),
+            name: game_name,
+            currency: currency_symbol(currency).to_string(),
+            full_price: format_price(full_price, currency),
+            discounted_price: if current_price < full_price { format_price(current_price, currency) } else { String::new() },
+            discount: discount_pct(full_price, current_price),
+            img_url: item["featured_image_small"].as_str().unwrap_or_default().to_string(),
+            url: format!("{}{}", HUMBLE_PRODUCT_URL, item["human_url"].as_str().unwrap_or_default()),
+            notes,
+            ..Default::default()
+        };
+        game_list.push(game);
+    }
+
+    info!("Humble Store search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
+
+    Ok(Scrape { games: game_list, containers, rows })
+}
+
+/// Whether the result says the game comes with Humble Choice or a bundle.
+fn humble_inclusion(item: &Value) -> Option<Note> {
+    let labels: Vec<&str> = ["cta_badge", "badge", "promotional_text"].iter()
+        .filter_map(|key| item[*key].as_str())
+        .collect();
+    if labels.iter().any(|label| label.contains("Choice")) {
+        Some(Note::HumbleChoice)
+    } else if labels.iter().any(|label| label.contains("Bundle")) {
+        Some(Note::HumbleBundle)
+    } else {
+        None
+    }
+}
+
+pub async fn get_game_microsoft(
+    fetcher: Fetcher,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const MICROSOFT_SEARCH_URL: &str = "https://displaycatalog.mp.microsoft.com/v7.0/productFamilies/autosuggest?productFamilyNames=Games&platformDependencyName=Windows.Desktop";
+    const MICROSOFT_PRODUCTS_URL: &str = "https://displaycatalog.mp.microsoft.com/v7.0/products?fieldsTemplate=details";
+    const MICROSOFT_PRODUCT_URL: &str = "https://www.microsoft.com/store/productId/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}&market={}&languages={}&query={}", &MICROSOFT_SEARCH_URL, region.country(), region.locale(), &game_param_encoded);
+
+    info!("MICROSOFT_SEARCH_URL call: {:#?}", url);
+
+    // The search only suggests titles, prices come from their products
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+    let product_ids: Vec<String> = response["ResultSets"].as_array().into_iter().flatten()
+        .flat_map(|set| set["Suggests"].as_array().into_iter().flatten())
+        .flat_map(|suggest| suggest["Metas"].as_array().into_iter().flatten())
+        .filter(|meta| meta["Key"].as_str() == Some("BigCatalogId"))
+        .filter_map(|meta| meta["Value"].as_str().map(str::to_string))
+        .unique()
+        .collect();
+    if product_ids.is_empty() {
+        // The API answered, there is just nothing to list
+        return Ok(Scrape { games: Vec::new(), containers: 1, rows: 0 });
+    }
+
+    let url: String = format!("{}&market={}&languages={}&bigIds={}", &MICROSOFT_PRODUCTS_URL, region.country(), region.locale(), product_ids.join(","));
+
+    info!("MICROSOFT_PRODUCTS_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+
+    // Game Pass only adds a note, the prices are still worth showing without it
+    let game_pass: Vec<String> = match game_pass_pc(&fetcher, region).await {
+        Ok(ids) => ids,
+        Err(e) => {
+            warn!("Could not read the PC Game Pass catalog: {}", e);
+            Vec::new()
+        }
     };
-    // let game_epic_opt: GamesVec = GamesVec {
-    //     games: get_game_epic(client.clone(), game.clone()).await?,
-    // };
-    let games_nuuvem: GamesVec = GamesVec {
-        games: get_game_nuuvem(client.clone(), game.clone()).await?,
+
+    let mut game_list: Vec<Game> = Vec::new();
+    let results = response["Products"].as_array();
+    let containers: usize = if results.is_some() { 1 } else { 0 };
+    let rows: usize = results.map(|r| r.len()).unwrap_or(0);
+
+    for item in results.into_iter().flatten() {
+        let product_id = item["ProductId"].as_str().unwrap_or_default();
+        let properties = &item["LocalizedProperties"][0];
+        let Some(price) = item["DisplaySkuAvailabilities"].as_array().into_iter().flatten()
+            .flat_map(|sku| sku["Availabilities"].as_array().into_iter().flatten())
+            .find(|availability| availability["Actions"].as_array().map_or(false, |actions| actions.iter().any(|a| a == "Purchase")))
+            .map(|availability| &availability["OrderManagementData"]["Price"])
+        else {
+            continue;
+        };
+        let currency = price["CurrencyCode"].as_str().unwrap_or_default();
+        let (Some(full_price), Some(current_price)) = (price["MSRP"].as_f64(), price["ListPrice"].as_f64()) else {
+            continue;
+        };
+
+        let img_url: String = properties["Images"].as_array().into_iter().flatten()
+            .find(|image| image["ImagePurpose"].as_str() == Some("BoxArt"))
+            .and_then(|image| image["Uri"].as_str())
+            .map(|uri| format!("https:{}", uri))
+            .unwrap_or_default();
+
+        let mut notes: Vec<Note> = Vec::new();
+        if game_pass.iter().any(|id| id == product_id) {
+            notes.push(Note::GamePass);
+        }
+
+        let game: Game = Game {
+            site: "Microsoft Store".to_string(),
+            product_id: product_id.to_string(),
+            name: properties["ProductTitle"].as_str().unwrap_or_default().to_string(),
+            currency: currency_symbol(currency).to_string(),
+            full_price: format_price(full_price, currency),
+            discounted_price: if current_price < full_price { format_price(current_price, currency) } else { String::new() },
+            discount: discount_pct(full_price, current_price),
+            img_url,
+            url: format!("{}{}", MICROSOFT_PRODUCT_URL, product_id),
+            notes,
+            ..Default::default()
+        };
+        game_list.push(game);
+    }
+
+    info!("Microsoft Store search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
+
+    Ok(Scrape { games: game_list, containers, rows })
+}
+
+/// The product ids currently in PC Game Pass.
+async fn game_pass_pc(fetcher: &Fetcher, region: Region) -> Result<Vec<String>, Error> {
+    const GAME_PASS_PC_URL: &str = "https://catalog.gamepass.com/sigls/v2?id=fdd9e2a7-0fee-49f6-ad69-4354098401ff";
+
+    let url: String = format!("{}&language={}&market={}", &GAME_PASS_PC_URL, region.locale().to_lowercase(), region.country());
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+
+    // The first entry describes the list itself and has no id
+    Ok(response.as_array().into_iter().flatten()
+        .filter_map(|entry| entry["id"].as_str().map(str::to_string))
+        .collect())
+}
+
+pub async fn get_game_fanatical(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    bundle_selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const FANATICAL_URL: &str = "https://www.fanatical.com/en/search?search=";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}", &FANATICAL_URL, &game_param_encoded);
+
+    info!("FANATICAL_URL call: {:#?}", url);
+
+    let headers = locale_headers(region);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, headers.clone()).await?;
+    let mut scrape = parse_listing("Fanatical", &selectors, &response_str, &url);
+
+    // Bundles with the game are listed on its product page, only the game
+    // searched for is looked up to keep it to one extra request
+    let games: GamesVec = GamesVec { games: scrape.games.clone() };
+    let matched: Option<Game> = search_in(&games, &game).game.or_else(|| scrape.games.first().cloned());
+    if let Some(matched) = matched.filter(|g| !g.url.is_empty()) {
+        match fetcher.fetch(&bundle_selectors, &matched.url, headers).await {
+            Ok(page) => {
+                let bundles = parse_bundles(&bundle_selectors, &page, &matched.url);
+                info!("Fanatical bundles with {}: {}", matched.name, bundles.len());
+                if let Some(g) = scrape.games.iter_mut().find(|g| g.url == matched.url) {
+                    g.bundles = bundles;
+                }
+            }
+            Err(e) => warn!("Could not read the Fanatical bundles of {}: {}", matched.name, e),
+        }
+    }
+
+    info!("Fanatical search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_playstation(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const PLAYSTATION_URL: &str = "https://store.playstation.com/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}/search/{}", &PLAYSTATION_URL, region.locale().to_lowercase(), &game_param_encoded);
+
+    info!("PLAYSTATION_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
+    let scrape = parse_listing("PlayStation Store", &selectors, &response_str, &url);
+
+    info!("PlayStation Store search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_nintendo(
+    fetcher: Fetcher,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const NINTENDO_SEARCH_URL: &str = "https://u3b6gr4ua3-dsn.algolia.net/1/indexes/store_game_en_us?x-algolia-application-id=U3B6GR4UA3&x-algolia-api-key=a29c6927638bfd8cee23993e51e721c9&hitsPerPage=20";
+    const NINTENDO_PRICE_URL: &str = "https://api.ec.nintendo.com/v1/price";
+    const NINTENDO_URL: &str = "https://www.nintendo.com";
+    const NINTENDO_IMAGE_URL: &str = "https://assets.nintendo.com/image/upload/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}&query={}", &NINTENDO_SEARCH_URL, &game_param_encoded);
+
+    info!("NINTENDO_SEARCH_URL call: {:#?}", url);
+
+    // The catalog is shared by the Americas, prices are asked for the region
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+    let hits: Vec<&Value> = response["hits"].as_array().into_iter().flatten()
+        .filter(|hit| hit["nsuid"].is_string())
+        .collect();
+    if hits.is_empty() {
+        // The API answered, there is just nothing to list
+        return Ok(Scrape { games: Vec::new(), containers: 1, rows: 0 });
+    }
+
+    let ids: Vec<&str> = hits.iter().filter_map(|hit| hit["nsuid"].as_str()).collect();
+    let url: String = format!(
+        "{}?country={}&lang={}&ids={}",
+        &NINTENDO_PRICE_URL, region.country(), &region.locale()[..2], ids.join(",")
+    );
+
+    info!("NINTENDO_PRICE_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+    let prices: Vec<&Value> = response["prices"].as_array().into_iter().flatten().collect();
+
+    let mut game_list: Vec<Game> = Vec::new();
+    let containers: usize = 1;
+    let rows: usize = hits.len();
+
+    for hit in hits {
+        let nsuid = hit["nsuid"].as_str().unwrap_or_default();
+        let Some(price) = prices.iter().find(|p| p["title_id"].to_string().trim_matches('"') == nsuid) else {
+            continue;
+        };
+        // Titles not sold in the region have no regular price
+        let currency = price["regular_price"]["currency"].as_str().unwrap_or_default();
+        let Some(full_price) = price["regular_price"]["raw_value"].as_str().and_then(|p| p.parse::<f64>().ok()) else {
+            continue;
+        };
+        let current_price = price["discount_price"]["raw_value"].as_str()
+            .and_then(|p| p.parse::<f64>().ok())
+            .unwrap_or(full_price);
+
+        let image = hit["productImage"].as_str().unwrap_or_default();
+        let img_url: String = if image.is_empty() || image.starts_with("http") {
+            image.to_string()
+        } else {
+            format!("{}{}", NINTENDO_IMAGE_URL, image)
+        };
+
+        let game: Game = Game {
+            site: "Nintendo eShop".to_string(),
+            product_id: nsuid.to_string(),
+            name: hit["title"].as_str().unwrap_or_default().to_string(),
+            currency: currency_symbol(currency).to_string(),
+            full_price: format_price(full_price, currency),
+            discounted_price: if current_price < full_price { format_price(current_price, currency) } else { String::new() },
+            discount: discount_pct(full_price, current_price),
+            img_url,
+            url: format!("{}{}", NINTENDO_URL, hit["url"].as_str().unwrap_or_default()),
+            ..Default::default()
+        };
+        game_list.push(game);
+    }
+
+    info!("Nintendo eShop search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
+
+    Ok(Scrape { games: game_list, containers, rows })
+}
+
+pub async fn get_game_ubisoft(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const UBISOFT_URL: &str = "https://store.ubisoft.com/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}/search?q={}", &UBISOFT_URL, region.country().to_lowercase(), &game_param_encoded);
+
+    info!("UBISOFT_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
+    let scrape = parse_listing("Ubisoft Store", &selectors, &response_str, &url);
+
+    info!("Ubisoft Store search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_ea(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const EA_URL: &str = "https://www.ea.com/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}/search?q={}", &EA_URL, region.locale().to_lowercase(), &game_param_encoded);
+
+    info!("EA_URL call: {:#?}", url);
+
+    let headers = locale_headers(region);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, headers).await?;
+    let scrape = parse_listing("EA app", &selectors, &response_str, &url);
+
+    info!("EA app search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_battlenet(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const BATTLENET_URL: &str = "https://shop.battle.net/";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}/search?q={}", &BATTLENET_URL, region.locale().to_lowercase(), &game_param_encoded);
+
+    info!("BATTLENET_URL call: {:#?}", url);
+
+    let headers = locale_headers(region);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, headers).await?;
+    let scrape = parse_listing("Battle.net", &selectors, &response_str, &url);
+
+    info!("Battle.net search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_kabum(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const KABUM_URL: &str = "https://www.kabum.com.br/busca/";
+
+    // Narrowed to PC digital keys, the catalog mixes games with hardware and discs
+    let game_param_encoded: String = byte_serialize(format!("{} pc digital", game).as_bytes()).collect();
+    let url: String = format!("{}{}", &KABUM_URL, &game_param_encoded);
+
+    info!("KABUM_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
+    let mut scrape = parse_listing("KaBuM!", &selectors, &response_str, &url);
+    scrape.games.retain_mut(|game| match retail_game_name(&game.name) {
+        Some(name) => {
+            game.name = name;
+            true
+        }
+        None => false,
+    });
+
+    info!("KaBuM! search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_amazon_br(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const AMAZON_BR_URL: &str = "https://www.amazon.com.br/s?i=videogames&k=";
+
+    // Narrowed to PC digital codes, the category also sells discs and accessories
+    let game_param_encoded: String = byte_serialize(format!("{} pc código digital", game).as_bytes()).collect();
+    let url: String = format!("{}{}", &AMAZON_BR_URL, &game_param_encoded);
+
+    info!("AMAZON_BR_URL call: {:#?}", url);
+
+    let headers = locale_headers(Region::Br);
+
+    let response_str: String = fetcher.fetch(&selectors, &url, headers).await?;
+    let mut scrape = parse_listing("Amazon Brasil", &selectors, &response_str, &url);
+
+    // Amazon lists the digital code as an edition of the game, as in "Game - Código Digital",
+    // console ones are left out
+    scrape.games.retain_mut(|game| match retail_game_name(&game.name) {
+        Some(name) => {
+            game.name = name;
+            true
+        }
+        None => false,
+    });
+    for game in scrape.games.iter_mut() {
+        if game.discount == "0%" {
+            if let (Some(full_price), Some(price)) = (parse_brl(&game.full_price), parse_brl(&game.discounted_price)) {
+                game.discount = discount_pct(full_price, price);
+            }
+        }
+    }
+
+    info!("Amazon Brasil search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+/// Asks for the region's locale, stores that price by the visitor's language
+/// then show the region's prices.
+fn locale_headers(region: Region) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static(region.locale()));
+    headers
+}
+
+/// Reads a price written as "R$ 1.299,90".
+fn parse_brl(text: &str) -> Option<f64> {
+    text.trim_start_matches("R$").trim().replace('.', "").replace(',', ".").parse().ok()
+}
+
+pub async fn get_game_itch(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    game_selectors: Arc<StoreSelectors>,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const ITCH_URL: &str = "https://itch.io/search?type=games&q=";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}", &ITCH_URL, &game_param_encoded);
+
+    info!("ITCH_URL call: {:#?}", url);
+
+    // itch.io sells in dollars everywhere
+    let response_str: String = fetcher.fetch(&selectors, &url, HeaderMap::new()).await?;
+    let document = Html::parse_document(&response_str);
+
+    let mut game_list: Vec<Game> = Vec::new();
+    let mut containers: usize = 0;
+    let mut rows: usize = 0;
+
+    for main_rows in document.select(&selectors.results) {
+        containers += 1;
+        for game_row in main_rows.select(&selectors.row) {
+            rows += 1;
+            let game_price = select_text(&game_row, &selectors.discounted_price);
+            let game_discount = select_text(&game_row, &selectors.discount);
+            let free: bool = game_price.is_empty();
+
+            let game: Game = Game {
+                site: "itch.io".to_string(),
+                name: select_text(&game_row, &selectors.title),
+                currency: if free { String::new() } else { select_currency(&game_row, &selectors.discounted_price) },
+                full_price: game_price,
+                discount: if game_discount.is_empty() { "0%".to_string() } else { game_discount.trim_start_matches('-').to_string() },
+                img_url: select_attr(&game_row, &selectors.image, &selectors.image_attr),
+                url: select_link(&game_row, &selectors.link, &selectors.link_attr, &url),
+                ..Default::default()
+            };
+            game_list.push(game);
+        }
+    }
+
+    // Minimum prices and sale ends are only on the game page, only the game
+    // searched for is looked up to keep it to one extra request
+    let games: GamesVec = GamesVec { games: game_list.clone() };
+    let matched: Option<Game> = search_in(&games, &game).game.or_else(|| game_list.first().cloned());
+    if let Some(matched) = matched.filter(|g| !g.url.is_empty()) {
+        match fetcher.fetch(&game_selectors, &matched.url, HeaderMap::new()).await {
+            Ok(page) => {
+                if let Some(g) = game_list.iter_mut().find(|g| g.url == matched.url) {
+                    read_itch_page(&game_selectors, &page, g);
+                }
+            }
+            Err(e) => warn!("Could not read the itch.io page of {}: {}", matched.name, e),
+        }
+    }
+
+    info!("itch.io search found: {:#?} (containers: {}, rows: {})", &game_list.len(), containers, rows);
+
+    Ok(Scrape { games: game_list, containers, rows })
+}
+
+/// Adds what only an itch.io game page tells to the game: the minimum of a
+/// "name your own price" game and when its sale ends.
+fn read_itch_page(selectors: &StoreSelectors, response_str: &str, game: &mut Game) {
+    let document = Html::parse_document(response_str);
+    let Some(page) = document.select(&selectors.results).flat_map(|r| r.select(&selectors.row)).next() else {
+        return;
     };
-    let games_gog: GamesVec = GamesVec {
-        games: get_game_gog(client.clone(), game.clone()).await?,
+
+    // Reads like "$5.00 USD or more", or just "Name your own price" when it can be free
+    let buy_message = select_text(&page, &selectors.discounted_price);
+    if buy_message.contains("or more") {
+        let minimum = buy_message.trim_end_matches("or more").trim();
+        game.notes.push(Note::NameYourPrice(minimum.to_string()));
+    } else if buy_message.contains("Name your own price") {
+        game.notes.push(Note::NameYourPriceFree);
+    } else if game.full_price.is_empty() {
+        game.notes.push(Note::Free);
+    }
+
+    let ends_text = match &selectors.ends_attr {
+        Some(attr) => select_attr(&page, &selectors.ends, attr),
+        None => select_text(&page, &selectors.ends),
     };
+    game.sale_end = parse_end_date(&ends_text);
+}
+
+pub async fn get_game_eneba(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    offer_selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const ENEBA_URL: &str = "https://www.eneba.com/store/all?text=";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}", &ENEBA_URL, &game_param_encoded);
+
+    info!("ENEBA_URL call: {:#?}", url);
+
+    let scrape = get_reseller_listing("Eneba", fetcher, &selectors, &offer_selectors, region, &url, &game).await?;
+
+    info!("Eneba search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+pub async fn get_game_kinguin(
+    fetcher: Fetcher,
+    selectors: Arc<StoreSelectors>,
+    offer_selectors: Arc<StoreSelectors>,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+	// API endpoint var
+    const KINGUIN_URL: &str = "https://www.kinguin.net/listing?platforms=2&phrase=";
+
+    let game_param_encoded: String = byte_serialize(game.as_bytes()).collect();
+    let url: String = format!("{}{}", &KINGUIN_URL, &game_param_encoded);
+
+    info!("KINGUIN_URL call: {:#?}", url);
+
+    let scrape = get_reseller_listing("Kinguin", fetcher, &selectors, &offer_selectors, region, &url, &game).await?;
+
+    info!("Kinguin search found: {:#?} (containers: {}, rows: {})", &scrape.games.len(), scrape.containers, scrape.rows);
+
+    Ok(scrape)
+}
+
+/// Reads a key marketplace search, where listings carry the key's region in
+/// their name, then the seller of the cheapest offer of the game searched for.
+async fn get_reseller_listing(
+    site: &str,
+    fetcher: Fetcher,
+    selectors: &StoreSelectors,
+    offer_selectors: &StoreSelectors,
+    region: Region,
+    url: &str,
+    game: &str,
+) -> Result<Scrape, Error> {
+    let headers = locale_headers(region);
+
+    let response_str: String = fetcher.fetch(selectors, url, headers.clone()).await?;
+    let mut scrape = parse_listing(site, selectors, &response_str, url);
+    for listing in scrape.games.iter_mut() {
+        let (name, region_lock) = split_key_listing(&listing.name);
+        listing.name = name;
+        listing.offer = Some(ResellerOffer { region_lock, ..Default::default() });
+    }
+
+    let games: GamesVec = GamesVec { games: scrape.games.clone() };
+    let Some(matched) = search_in(&games, game).game.filter(|g| !g.url.is_empty()) else {
+        return Ok(scrape);
+    };
+    match fetcher.fetch(offer_selectors, &matched.url, headers).await {
+        Ok(page) => {
+            let document = Html::parse_document(&page);
+            // Offers are listed cheapest first
+            let cheapest = document.select(&offer_selectors.results).flat_map(|r| r.select(&offer_selectors.row)).next();
+            if let (Some(cheapest), Some(listing)) = (cheapest, scrape.games.iter_mut().find(|g| g.url == matched.url)) {
+                let price = select_text(&cheapest, &offer_selectors.discounted_price);
+                if !price.is_empty() {
+                    listing.discounted_price = price;
+                }
+                if let Some(offer) = listing.offer.as_mut() {
+                    offer.seller = select_text(&cheapest, &offer_selectors.seller);
+                    offer.seller_rating = select_text(&cheapest, &offer_selectors.seller_rating);
+                }
+            }
+        }
+        Err(e) => warn!("Could not read the {} offers of {}: {}", site, matched.name, e),
+    }
+
+    Ok(scrape)
+}
+
+/// Reads the bundles listed on a product page.
+fn parse_bundles(selectors: &StoreSelectors, response_str: &str, url: &str) -> Vec<Bundle> {
+    let document = Html::parse_document(response_str);
+
+    let mut bundles: Vec<Bundle> = Vec::new();
+    for section in document.select(&selectors.results) {
+        for row in section.select(&selectors.row) {
+            let ends_text = match &selectors.ends_attr {
+                Some(attr) => select_attr(&row, &selectors.ends, attr),
+                None => select_text(&row, &selectors.ends),
+            };
+            bundles.push(Bundle {
+                name: select_text(&row, &selectors.title),
+                price: select_text(&row, &selectors.discounted_price),
+                ends: parse_end_date(&ends_text),
+                url: select_link(&row, &selectors.link, &selectors.link_attr, url),
+            });
+        }
+    }
+    bundles
+}
+
+/// Reads a search page laid out as rows with a full price, a discounted
+/// price and a discount, which is how most storefronts list results.
+pub fn parse_listing(site: &str, selectors: &StoreSelectors, response_str: &str, url: &str) -> Scrape {
+    let document = Html::parse_document(response_str);
+
+    let mut game_list: Vec<Game> = Vec::new();
+    let mut containers: usize = 0;
+    let mut rows: usize = 0;
+
+    for main_rows in document.select(&selectors.results) {
+        containers += 1;
+        for game_row in main_rows.select(&selectors.row) {
+            rows += 1;
+            let game_name = select_text(&game_row, &selectors.title);
+            let game_full_price = select_text(&game_row, &selectors.full_price);
+            let game_discounted_price = select_text(&game_row, &selectors.discounted_price);
+            let game_discount = select_text(&game_row, &selectors.discount);
+            let mut game_currency = select_currency(&game_row, &selectors.currency);
+            if game_currency.is_empty() {
+                game_currency = select_currency(&game_row, &selectors.discounted_price);
+            }
+            let game_img_url = select_attr(&game_row, &selectors.image, &selectors.image_attr);
+            let game_url = select_link(&game_row, &selectors.link, &selectors.link_attr, url);
+
+            if !game_discounted_price.is_empty() || !game_full_price.is_empty() {
+                let game: Game = Game {
+                    site: site.to_string(),
+                    name: game_name,
+                    currency: game_currency,
+                    full_price: game_full_price,
+                    discounted_price: game_discounted_price,
+                    discount: if game_discount.is_empty() { "0%".to_string() } else { game_discount },
+                    img_url: game_img_url,
+                    url: game_url,
+                    ..Default::default()
+                };
+                game_list.push(game);
+            }
+        }
+    }
 
-    // Set game name for the first found in steam, or then nuuvem
-    let mut game_name: String = game.clone();
+    Scrape { games: game_list, containers, rows }
+}
+
+/// Runs the search of the given store with its configured selectors.
+pub async fn scrape(
+    store: Store,
+    fetcher: Fetcher,
+    selectors: &SelectorConfig,
+    region: Region,
+    game: String,
+) -> Result<Scrape, Error> {
+    let mut scrape = match store {
+        Store::Steam => get_game_steam(fetcher, selectors.store("steam"), region, game).await,
+        Store::Epic => get_game_epic(fetcher, selectors.store("epic"), region, game).await,
+        Store::Nuuvem => get_game_nuuvem(fetcher, selectors.store("nuuvem"), region, game).await,
+        Store::Gog => get_game_gog(fetcher, selectors.store("gog"), region, game).await,
+        Store::Gmg => get_game_gmg(fetcher, selectors.store("gmg"), region, game).await,
+        Store::Humble => get_game_humble(fetcher, region, game).await,
+        Store::Microsoft => get_game_microsoft(fetcher, region, game).await,
+        Store::PlayStation => get_game_playstation(fetcher, selectors.store("playstation"), region, game).await,
+        Store::Nintendo => get_game_nintendo(fetcher, region, game).await,
+        Store::Ubisoft => get_game_ubisoft(fetcher, selectors.store("ubisoft"), region, game).await,
+        Store::Ea => get_game_ea(fetcher, selectors.store("ea"), region, game).await,
+        Store::BattleNet => get_game_battlenet(fetcher, selectors.store("battlenet"), region, game).await,
+        Store::Kabum => get_game_kabum(fetcher, selectors.store("kabum"), game).await,
+        Store::AmazonBr => get_game_amazon_br(fetcher, selectors.store("amazon_br"), game).await,
+        Store::Eneba => get_game_eneba(fetcher, selectors.store("eneba"), selectors.store("eneba_offers"), region, game).await,
+        Store::Kinguin => get_game_kinguin(fetcher, selectors.store("kinguin"), selectors.store("kinguin_offers"), region, game).await,
+        Store::Itch => get_game_itch(fetcher, selectors.store("itch"), selectors.store("itch_game"), game).await,
+        Store::Fanatical => get_game_fanatical(fetcher, selectors.store("fanatical"), selectors.store("fanatical_bundles"), region, game).await,
+    }?;
+
+    // What a listing does not tell comes from the store and the name
+    for game in scrape.games.iter_mut() {
+        if game.drm.is_empty() {
+            game.drm = store.drm().unwrap_or_default().to_string();
+        }
+        if game.platforms.is_empty() {
+            game.platforms.push(store.platform().name().to_string());
+        }
+        if game.edition.is_empty() {
+            game.edition = edition_of(&game.name);
+        }
+    }
+    Ok(scrape)
+}
+
+/// Runs the search of the given store, unless the same search was done
+/// recently or is already running, in which case its result is shared.
+///
+/// Transient failures are retried, and a store that keeps failing is skipped
+/// by its circuit breaker until its cool-down is over.
+pub async fn cached_scrape(
+    data: &Data,
+    store: Store,
+    region: Region,
+    game: &str,
+) -> Result<CachedScrape, Error> {
+    let key = CacheKey { store, region, query: normalize_query(game) };
+    let fetcher = data.0.fetcher.clone();
+    let selectors = data.selectors();
+    data.0.cache.get_or_fetch(key, || async move {
+        data.0.breaker.allow(store)?;
+        let what = format!("{} search for '{}'", store, game);
+        let result = data.0.retry
+            .run(&what, || scrape(store, fetcher.clone(), &selectors, region, game.to_string()))
+            .await;
+        match &result {
+            Ok(_) => data.0.breaker.record_success(store),
+            Err(e) => data.0.breaker.record_failure(store, &e.to_string()),
+        }
+        result
+    }).await
+}
+
+/// The prices of one game across the stores searched.
+pub struct Comparison {
+    pub game_name: String,
+    pub img_url: String,
+    pub fields: Vec<(String, String, bool)>,
+    /// When the oldest of the prices shown was fetched.
+    pub oldest_fetch: Option<DateTime<Utc>>,
+    /// The game as listed by each official store that has it.
+    pub listings: Vec<(Store, Game)>,
+}
+
+/// The stores the guild compares for the platform, and the market they are searched in.
+fn searched_stores(data: &Data, guild_id: Option<serenity::GuildId>, platform: PlatformFilter) -> (Region, Vec<Store>) {
+    let settings: GuildSettings = data.0.settings.get(guild_id);
+    let region: Region = settings.region.unwrap_or(data.0.region);
+    let stores: Vec<Store> = Store::ALL.into_iter()
+        .filter(|store| platform.includes(*store) && store.sells_in(region))
+        .filter(|store| settings.searches(*store))
+        .collect();
+    (region, stores)
+}
+
+/// Whether every store would answer the search from the cache.
+pub fn is_cached(data: &Data, guild_id: Option<serenity::GuildId>, game: &str, platform: PlatformFilter) -> bool {
+    let (region, stores) = searched_stores(data, guild_id, platform);
+    stores.into_iter().all(|store| data.0.cache.is_fresh(&CacheKey { store, region, query: normalize_query(game) }))
+}
+
+/// Searches every store the guild compares at once, a failing store only
+/// affects its own result.
+pub async fn search_stores(
+    data: &Data,
+    guild_id: Option<serenity::GuildId>,
+    game: &str,
+    platform: PlatformFilter,
+) -> Vec<(Store, Result<CachedScrape, Error>)> {
+    let (region, stores) = searched_stores(data, guild_id, platform);
+    let searches: Vec<_> = stores.iter().map(|store| cached_scrape(data, *store, region, game)).collect();
+    stores.into_iter()
+        .zip(join_all(searches).await)
+        .collect()
+}
+
+/// Compares the prices of the game searched for across the stores.
+pub async fn compare(
+    data: &Data,
+    guild_id: Option<serenity::GuildId>,
+    lang: Lang,
+    game: &str,
+    platform: PlatformFilter,
+) -> Comparison {
+    let results = search_stores(data, guild_id, game, platform).await;
+    let oldest_fetch: Option<DateTime<Utc>> = results.iter()
+        .filter_map(|(_, result)| result.as_ref().ok())
+        .map(|cached| cached.fetched_at)
+        .min();
+
+    // A game searched before is matched in every store by its known names and
+    // products, otherwise set game name for the first found in steam, or then
+    // nuuvem, or then gog, or then in the first store searched
+    let known: Option<CatalogGame> = data.0.catalog.resolve(game);
+    let mut game_name: String = known.as_ref().map_or_else(|| game.to_string(), |known| known.name.clone());
     let mut img_url: String = String::new();
-    if !games_steam.games.is_empty() {
-        game_name = games_steam.games.first().unwrap().name.clone();
-        img_url = games_steam.games.first().unwrap().img_url.clone()
-    } else if !games_nuuvem.games.is_empty() {
-        game_name = games_nuuvem.games.first().unwrap().name.clone();
-        img_url = games_nuuvem.games.first().unwrap().img_url.clone();
-    } else if !games_nuuvem.games.is_empty() {
-        game_name = games_gog.games.first().unwrap().name.clone();
-        img_url = games_gog.games.first().unwrap().img_url.clone();
-    }
-
-    let game_steam_opt: GameOpt = search_in(&games_steam, &game_name);
-    let game_nuuvem_opt: GameOpt = search_in(&games_nuuvem, &game_name);
-    let game_gog_opt: GameOpt = search_in(&games_gog, &game_name);
-    site_games.insert(sites[0], game_steam_opt);
-    site_games.insert(sites[2], game_nuuvem_opt);
-    site_games.insert(sites[3], game_gog_opt);
+    let preferred = [Store::Steam, Store::Nuuvem, Store::Gog].into_iter()
+        .chain(results.iter().map(|(store, _)| *store).filter(|store| !store.is_reseller()));
+    for store in preferred {
+        let first: Option<Game> = results.iter()
+            .filter(|(s, _)| *s == store)
+            .filter_map(|(_, result)| result.as_ref().ok())
+            .find_map(|cached| match &known {
+                Some(known) => known.find_in(&GamesVec { games: cached.scrape.games.clone() }, store).game,
+                None => cached.scrape.games.first().cloned(),
+            });
+        if let Some(first) = first {
+            if known.is_none() {
+                game_name = first.name.clone();
+            }
+            img_url = first.img_url.clone();
+            break;
+        }
+    }
+
+    // The game as listed by each store that has it
+    let matches: Vec<(Store, Game)> = results.iter()
+        .filter_map(|(store, result)| Some((*store, result.as_ref().ok()?)))
+        .filter_map(|(store, cached)| {
+            let games: GamesVec = GamesVec { games: cached.scrape.games.clone() };
+            let game_opt: GameOpt = match &known {
+                Some(known) => known.find_in(&games, store),
+                None => search_in(&games, &game_name),
+            };
+            Some((store, game_opt.game?))
+        })
+        .collect();
+    let matched = |store: Store| GameOpt { game: matches.iter().find(|(s, _)| *s == store).map(|(_, g)| g.clone()) };
 
     // Build response fields
-    let mut fields_vec: Vec<(String, GameOpt, bool)> = Vec::default();
-    for (site, game_opt) in site_games {
-        fields_vec.push((site.to_string(), game_opt, true));
+    let mut fields_vec: Vec<(String, String, bool)> = Vec::default();
+    for (store, result) in results.iter().filter(|(store, _)| !store.is_reseller()) {
+        let value = match result {
+            Ok(_) => {
+                let game_opt: GameOpt = matched(*store);
+                if game_opt.game.is_none() && store.is_publisher() {
+                    continue;
+                }
+                game_opt.text(lang)
+            }
+            Err(e) => {
+                error!("{} search failed: {}", store, e);
+                degraded_text(data, lang, *store)
+            }
+        };
+        fields_vec.push((store.to_string(), value, true));
     }
     fields_vec.sort_by(|a, b| b.0.cmp(&a.0));
 
-    ctx.send(|builder| {
-        builder
-        .content("").embed(|e| {
-            e.title(&game_name)
-            .image(img_url)
-            .fields(fields_vec)
+    // Bundles go on their own line after the stores
+    let bundles: Vec<String> = matches.iter()
+        .flat_map(|(_, g)| g.bundles.iter().map(move |bundle| format!("{}: {}", g.site, bundle.text(lang))))
+        .collect();
+    if !bundles.is_empty() {
+        fields_vec.push((tr(lang, "deal.bundles"), bundles.join("\n"), false));
+    }
+
+    // Key resellers are set apart so they are never taken for official stores
+    let offers: Vec<String> = results.iter()
+        .filter(|(store, _)| store.is_reseller())
+        .map(|(store, result)| match result {
+            Ok(_) => match matched(*store).game {
+                Some(g) => format!("**{}**: {}", store, reseller_text(lang, &g)),
+                None => format!("**{}**: {}", store, tr(lang, "price.not_found")),
+            },
+            Err(e) => {
+                error!("{} search failed: {}", store, e);
+                format!("**{}**: {}", store, degraded_text(data, lang, *store).replace('\n', ", "))
+            }
         })
+        .collect();
+    if !offers.is_empty() {
+        fields_vec.push((tr(lang, "deal.resellers"), offers.join("\n"), false));
+    }
+
+    // Official stores agreeing on the game teach the catalog its names and products
+    let listings: Vec<(Store, Game)> = matches.into_iter().filter(|(store, _)| !store.is_reseller()).collect();
+    if !listings.is_empty() {
+        if let Err(e) = data.0.catalog.record(&game_name, game, &listings).await {
+            error!("Could not record {} in the catalog: {}", game_name, e);
+        }
+    }
+
+    Comparison { game_name, img_url, fields: fields_vec, oldest_fetch, listings }
+}
+
+pub fn comparison_embed<'a>(e: &'a mut serenity::CreateEmbed, lang: Lang, comparison: &Comparison) -> &'a mut serenity::CreateEmbed {
+    e.title(&comparison.game_name)
+    .image(&comparison.img_url)
+    .fields(comparison.fields.clone())
+    .footer(|f| f.text(comparison.oldest_fetch.map(|fetched_at| cache_age(lang, fetched_at)).unwrap_or_default()))
+}
+
+/// Everything known about the game in one store.
+pub fn detail_embed<'a>(e: &'a mut serenity::CreateEmbed, lang: Lang, store: Store, game: &Game) -> &'a mut serenity::CreateEmbed {
+    let or_dash = |text: &str| if text.is_empty() { "—".to_string() } else { text.to_string() };
+    e.title(tr_args(lang, "details.title", &[("game", &game.name), ("store", &store)]))
+    .url(&game.url)
+    .thumbnail(&game.img_url)
+    .field(tr(lang, "details.original_price"), if game.discounted_price.is_empty() { game.price(lang) } else { or_dash(&game.full_price) }, true)
+    .field(tr(lang, "details.sale_price"), if game.discounted_price.is_empty() { tr(lang, "details.not_on_sale") } else { game.discounted_price.clone() }, true)
+    .field(tr(lang, "details.discount"), or_dash(&game.discount), true)
+    .field(tr(lang, "details.sale_ends"), game.sale_end.map_or("—".to_string(), |end| format!("<t:{}:f> (<t:{}:R>)", end.timestamp(), end.timestamp())), true)
+    .field(tr(lang, "details.edition"), if game.edition.is_empty() { tr(lang, "details.standard") } else { game.edition.clone() }, true)
+    .field(tr(lang, "details.drm"), or_dash(&game.drm), true)
+    .field(tr(lang, "details.platforms"), or_dash(&game.platforms.join(", ")), true)
+    .field(tr(lang, "details.link"), if game.url.is_empty() { "—".to_string() } else { format!("[{}]({})", tr_args(lang, "details.open_in", &[("store", &store)]), game.url) }, true);
+    if !game.notes.is_empty() {
+        e.description(game.notes.iter().map(|note| note.text(lang)).collect::<Vec<_>>().join("\n"));
+    }
+    e
+}
+
+/// How long the store menu of a `deal` reply keeps working.
+const DETAILS_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[poise::command(prefix_command, slash_command, reuse_response, track_edits)]
+pub async fn deal(
+    ctx: Context<'_>, 
+    game: String, 
+    #[description = "Which stores to compare, PC by default"] platform: Option<PlatformFilter>,
+) -> CommandResult {
+    let start = Instant::now();
+    let platform: PlatformFilter = platform.unwrap_or_default();
+    info!("Commands parameters: {{Game: {:#?}, Platform: {:?}}}", &game, platform);
+
+    // Only searches that reach the stores count against the cooldowns
+    if !is_cached(ctx.data(), ctx.guild_id(), &game, platform) && !cooldown::allow(ctx).await? {
+        return Ok(());
+    }
+
+    let lang: Lang = lang(ctx);
+    let comparison: Comparison = compare(ctx.data(), ctx.guild_id(), lang, &game, platform).await;
+    let menu_id = format!("{}:details", ctx.id());
+
+    let reply = ctx.send(|builder| {
+        builder
+        .content("").embed(|e| comparison_embed(e, lang, &comparison));
+        // The summary stays compact, a store's details are shown when picked
+        if !comparison.listings.is_empty() {
+            builder.components(|c| {
+                c.create_action_row(|row| {
+                    row.create_select_menu(|menu| {
+                        menu.custom_id(&menu_id)
+                        .placeholder(tr(lang, "deal.details_placeholder"))
+                        .options(|options| {
+                            for (store, listing) in comparison.listings.iter().take(25) {
+                                options.create_option(|o| o.label(store.name()).value(store.key()).description(&listing.name.chars().take(100).collect::<String>()));
+                            }
+                            options
+                        })
+                    })
+                })
+            });
+        }
+        builder
     })
     .await?;
 
     let duration: Duration = start.elapsed();
     info!("Time elapsed in deal command is: {:?}", duration);
 
+    announce(ctx, lang, &comparison).await;
+
+    if comparison.listings.is_empty() {
+        return Ok(());
+    }
+    let filter_id = menu_id.clone();
+    while let Some(press) = serenity::CollectComponentInteraction::new(ctx.serenity_context())
+        .filter(move |press| press.data.custom_id == filter_id)
+        .timeout(DETAILS_TIMEOUT)
+        .await
+    {
+        let picked = press.data.values.first().cloned().unwrap_or_default();
+        let Some((store, listing)) = comparison.listings.iter().find(|(store, _)| store.key() == picked) else { continue };
+        press.create_interaction_response(ctx.serenity_context(), |r| {
+            r.kind(serenity::InteractionResponseType::ChannelMessageWithSource)
+            .interaction_response_data(|d| d.ephemeral(true).embed(|e| detail_embed(e, lang, *store, listing)))
+        })
+        .await?;
+    }
+
+    // The menu no longer does anything once the collector is gone
+    reply.edit(ctx, |builder| builder.components(|c| c)).await?;
+
     Ok(())
 }
 
+/// Shares a comparison in the guild's announcement channel when an official
+/// store has the game at the guild's minimum discount or more.
+async fn announce(ctx: Context<'_>, lang: Lang, comparison: &Comparison) {
+    let settings: GuildSettings = ctx.data().0.settings.get(ctx.guild_id());
+    let Some(channel) = settings.announcement_channel.map(serenity::ChannelId) else { return };
+    let best: u8 = comparison.listings.iter().map(|(_, game)| parse_discount(&game.discount)).max().unwrap_or(0);
+    if channel == ctx.channel_id() || best == 0 || best < settings.min_discount {
+        return;
+    }
+
+    let content = tr_args(lang, "deal.announcement", &[("user", &format!("<@{}>", ctx.author().id)), ("discount", &best)]);
+    if let Err(e) = channel.send_message(ctx.serenity_context(), |m| {
+        m.content(content).embed(|embed| comparison_embed(embed, lang, comparison))
+    })
+    .await {
+        error!("Could not announce {} in channel {}: {}", comparison.game_name, channel, e);
+    }
+}
+
+/// The lowest offer of a key reseller, with what tells whether to trust it.
+fn reseller_text(lang: Lang, game: &Game) -> String {
+    let mut text = game.price(lang);
+    if let Some(offer) = &game.offer {
+        if !offer.region_lock.is_empty() {
+            text.push_str(&format!(" · {}", tr_args(lang, "deal.region_key", &[("region", &offer.region_lock)])));
+        }
+        if !offer.seller.is_empty() {
+            text.push_str(&format!(" · {}", tr_args(lang, "deal.sold_by", &[("seller", &offer.seller)])));
+            if !offer.seller_rating.is_empty() {
+                text.push_str(&format!(" ({})", offer.seller_rating));
+            }
+        }
+    }
+    text
+}
+
+/// What to show for a store whose search failed.
+fn degraded_text(data: &Data, lang: Lang, store: Store) -> String {
+    match data.0.breaker.state(store) {
+        CircuitState::Open { probe_at, .. } => tr_args(lang, "deal.degraded", &[("when", &format!("<t:{}:R>", probe_at.timestamp()))]),
+        CircuitState::Closed => tr(lang, "deal.unavailable"),
+    }
+}
+
+/// Tells how old the prices shown are.
+fn cache_age(lang: Lang, fetched_at: DateTime<Utc>) -> String {
+    let age = (Utc::now() - fetched_at).num_seconds();
+    match age {
+        0..=5 => tr(lang, "deal.fetched_now"),
+        6..=119 => tr_args(lang, "deal.cached_seconds", &[("seconds", &age)]),
+        _ => tr_args(lang, "deal.cached_minutes", &[("minutes", &(age / 60))]),
+    }
+}
+
 pub fn commands() -> [Command; 1] {
     [deal()]
 }
\ No newline at end of file
@@ -0,0 +1,185 @@
+use poise::serenity_prelude as serenity;
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::Context;
+use crate::commands::scraper::{compare, comparison_embed, is_cached, search_stores, Comparison};
+use crate::cooldown;
+use crate::funcs::normalize_title;
+use crate::i18n::{lang, tr, tr_args, Lang};
+use crate::structs::{Command, CommandResult, Game, PlatformFilter, Store};
+
+/// Results shown on each page, one "compare" button each.
+const PAGE_SIZE: usize = 5;
+
+/// How long the buttons keep working after the last press.
+const BUTTONS_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// One title as listed by the stores that have it.
+struct SearchEntry {
+    name: String,
+    listings: Vec<(Store, Game)>,
+}
+
+impl SearchEntry {
+    fn prices(&self, lang: Lang) -> String {
+        self.listings.iter()
+            .map(|(store, game)| format!("{}: {}", store, game.price(lang)))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// Lists every result of the stores, with buttons to page through them and
+/// to compare one of them
+#[poise::command(prefix_command, slash_command)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "Game to search for"] query: String,
+    #[description = "Which stores to search, PC by default"] platform: Option<PlatformFilter>,
+) -> CommandResult {
+    let lang: Lang = lang(ctx);
+    let platform: PlatformFilter = platform.unwrap_or_default();
+    info!("Commands parameters: {{Query: {:#?}, Platform: {:?}}}", &query, platform);
+
+    // Only searches that reach the stores count against the cooldowns
+    if !is_cached(ctx.data(), ctx.guild_id(), &query, platform) && !cooldown::allow(ctx).await? {
+        return Ok(());
+    }
+    ctx.defer().await?;
+
+    // The same title from several stores is a single entry
+    let mut entries: Vec<SearchEntry> = Vec::new();
+    for (store, result) in search_stores(ctx.data(), ctx.guild_id(), &query, platform).await {
+        let Ok(cached) = result else { continue };
+        if store.is_reseller() {
+            continue;
+        }
+        for game in &cached.scrape.games {
+            let title = normalize_title(&game.name);
+            match entries.iter_mut().find(|entry| normalize_title(&entry.name) == title) {
+                Some(entry) if !entry.listings.iter().any(|(s, _)| *s == store) => entry.listings.push((store, game.clone())),
+                Some(_) => {}
+                None => entries.push(SearchEntry { name: game.name.clone(), listings: vec![(store, game.clone())] }),
+            }
+        }
+    }
+    if entries.is_empty() {
+        ctx.say(tr_args(lang, "search.no_results", &[("query", &query)])).await?;
+        return Ok(());
+    }
+
+    let pages: usize = entries.len().div_ceil(PAGE_SIZE);
+    let mut page: usize = 0;
+    let id = ctx.id();
+
+    let reply = ctx.send(|builder| {
+        builder
+        .embed(|e| page_embed(e, lang, &query, &entries, page, pages))
+        .components(|c| page_buttons(c, lang, id, &entries, page, pages))
+    })
+    .await?;
+
+    let prefix = format!("{}:", id);
+    while let Some(press) = serenity::CollectComponentInteraction::new(ctx.serenity_context())
+        .filter(move |press| press.data.custom_id.starts_with(&prefix))
+        .timeout(BUTTONS_TIMEOUT)
+        .await
+    {
+        let action = press.data.custom_id.split(':').nth(1).unwrap_or_default();
+        match action {
+            "prev" | "next" => {
+                page = if action == "prev" { page.saturating_sub(1) } else { (page + 1).min(pages - 1) };
+                press.create_interaction_response(ctx.serenity_context(), |r| {
+                    r.kind(serenity::InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.embed(|e| page_embed(e, lang, &query, &entries, page, pages))
+                        .components(|c| page_buttons(c, lang, id, &entries, page, pages))
+                    })
+                })
+                .await?;
+            }
+            index => {
+                let Some(entry) = index.parse::<usize>().ok().and_then(|i| entries.get(i)) else { continue };
+                // A press reaches every store just like a search does
+                if !is_cached(ctx.data(), ctx.guild_id(), &entry.name, platform) && !cooldown::allow_press(ctx, &press).await? {
+                    continue;
+                }
+                // Comparing takes a while, acknowledge the press before answering
+                press.defer(ctx.serenity_context()).await?;
+                let comparison: Comparison = compare(ctx.data(), ctx.guild_id(), lang, &entry.name, platform).await;
+                if let Err(e) = press.create_followup_message(ctx.serenity_context(), |f| {
+                    f.embed(|e| comparison_embed(e, lang, &comparison))
+                })
+                .await {
+                    error!("Could not send the comparison of {}: {}", entry.name, e);
+                }
+            }
+        }
+    }
+
+    // Buttons no longer do anything once the collector is gone
+    reply.edit(ctx, |builder| builder.components(|c| c)).await?;
+
+    Ok(())
+}
+
+fn page_embed<'a>(
+    e: &'a mut serenity::CreateEmbed,
+    lang: Lang,
+    query: &str,
+    entries: &[SearchEntry],
+    page: usize,
+    pages: usize,
+) -> &'a mut serenity::CreateEmbed {
+    let start = page * PAGE_SIZE;
+    let fields: Vec<(String, String, bool)> = entries.iter()
+        .enumerate()
+        .skip(start)
+        .take(PAGE_SIZE)
+        .map(|(i, entry)| (format!("{}. {}", i + 1, entry.name), entry.prices(lang), false))
+        .collect();
+    e.title(tr_args(lang, "search.title", &[("query", &query)]))
+    .fields(fields)
+    .footer(|f| f.text(tr_args(lang, "search.footer", &[("page", &(page + 1)), ("pages", &pages), ("results", &entries.len())])))
+}
+
+fn page_buttons<'a>(
+    c: &'a mut serenity::CreateComponents,
+    lang: Lang,
+    id: u64,
+    entries: &[SearchEntry],
+    page: usize,
+    pages: usize,
+) -> &'a mut serenity::CreateComponents {
+    let start = page * PAGE_SIZE;
+    let end = (start + PAGE_SIZE).min(entries.len());
+    c.create_action_row(|row| {
+        for i in start..end {
+            row.create_button(|b| {
+                b.custom_id(format!("{}:{}", id, i))
+                .label(tr_args(lang, "search.compare", &[("number", &(i + 1))]))
+                .style(serenity::ButtonStyle::Primary)
+            });
+        }
+        row
+    })
+    .create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(format!("{}:prev", id))
+            .label(tr(lang, "search.previous"))
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{}:next", id))
+            .label(tr(lang, "search.next"))
+            .style(serenity::ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages)
+        })
+    })
+}
+
+pub fn commands() -> [Command; 1] {
+    [search()]
+}
@@ -0,0 +1,52 @@
+use crate::Context;
+use crate::circuit::CircuitState;
+use crate::cooldown;
+use crate::health::{alert, check_all};
+use crate::i18n::{lang, tr, tr_args, Lang};
+use crate::structs::{Command, CommandResult};
+
+/// Runs the canary search of every store and reports which ones work
+#[poise::command(prefix_command, slash_command)]
+pub async fn status(ctx: Context<'_>) -> CommandResult {
+    // Every store is searched, so it has the cooldowns of the searches
+    if !cooldown::allow(ctx).await? {
+        return Ok(());
+    }
+    ctx.defer().await?;
+    let lang: Lang = lang(ctx);
+
+    let (reports, changed) = check_all(ctx.data()).await;
+    if let Some(channel) = ctx.data().0.admin_channel_id {
+        alert(ctx.serenity_context(), channel, &changed).await;
+    }
+
+    let fields: Vec<(String, String, bool)> = reports.iter()
+        .map(|report| {
+            let checked = tr_args(lang, "status.checked", &[("when", &format!("<t:{}:R>", report.checked_at.timestamp()))]);
+            let mut value = format!("{} {}\n{}", report.health.emoji(), report.health.text(lang), checked);
+            if let CircuitState::Open { failures, probe_at, last_error } = ctx.data().0.breaker.state(report.store) {
+                let until = format!("<t:{}:R>", probe_at.timestamp());
+                value.push_str(&format!("\n{}", tr_args(lang, "status.degraded", &[("failures", &failures), ("when", &until)])));
+                if let Some(last_error) = last_error {
+                    value.push_str(&format!("\n{}", tr_args(lang, "status.last_error", &[("error", &last_error)])));
+                }
+            }
+            (report.store.to_string(), value, false)
+        })
+        .collect();
+
+    ctx.send(|builder| {
+        builder
+        .content("").embed(|e| {
+            e.title(tr(lang, "status.title"))
+            .fields(fields)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+pub fn commands() -> [Command; 1] {
+    [status()]
+}
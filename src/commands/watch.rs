@@ -0,0 +1,287 @@
+use serde_json::{json, Value};
+use reqwest::header::HeaderMap;
+use tracing::{info, warn};
+use url::form_urlencoded::byte_serialize;
+
+use crate::Context;
+use crate::cache::CacheKey;
+use crate::catalog::CatalogGame;
+use crate::commands::scraper::cached_scrape;
+use crate::cooldown;
+use crate::fetcher::Fetcher;
+use crate::funcs::{normalize_query, search_in};
+use crate::i18n::{lang, tr, tr_args, Lang};
+use crate::structs::{Command, CommandResult, Data, Error, Game, GamesVec, Region, Store};
+
+/// Games an embed field lists before summing up the rest.
+const LISTED: usize = 15;
+
+/// Follows games across stores
+#[poise::command(prefix_command, slash_command, subcommands("add", "list", "remove", "import_steam"))]
+pub async fn watch(_ctx: Context<'_>) -> CommandResult {
+    Ok(())
+}
+
+/// Adds a game to your watchlist
+#[poise::command(prefix_command, slash_command)]
+pub async fn add(
+    ctx: Context<'_>,
+    #[description = "Game to watch"] game: String,
+) -> CommandResult {
+    let data: &Data = ctx.data();
+    let lang: Lang = lang(ctx);
+    let region: Region = data.region(ctx.guild_id());
+
+    // Games the catalog knows or Steam was just searched for do not reach the store
+    let cached = data.0.catalog.resolve(&game).is_some()
+        || data.0.cache.is_fresh(&CacheKey { store: Store::Steam, region, query: normalize_query(&game) });
+    if !cached && !cooldown::allow(ctx).await? {
+        return Ok(());
+    }
+    ctx.defer().await?;
+
+    let Some(found) = find_game(data, region, &game).await? else {
+        ctx.say(tr_args(lang, "watch.not_found", &[("game", &game)])).await?;
+        return Ok(());
+    };
+    let added = data.0.watchlists.add(ctx.author().id, &[found.id]).await?;
+    if added.is_empty() {
+        ctx.say(tr_args(lang, "watch.already_watched", &[("game", &found.name)])).await?;
+    } else {
+        ctx.say(tr_args(lang, "watch.added", &[("game", &found.name)])).await?;
+    }
+    Ok(())
+}
+
+/// Lists the games you watch
+#[poise::command(prefix_command, slash_command)]
+pub async fn list(ctx: Context<'_>) -> CommandResult {
+    let data: &Data = ctx.data();
+    let lang: Lang = lang(ctx);
+    let entries = data.0.watchlists.list(ctx.author().id);
+    if entries.is_empty() {
+        ctx.say(tr(lang, "watch.empty")).await?;
+        return Ok(());
+    }
+
+    let names: Vec<String> = entries.iter()
+        .filter_map(|entry| data.0.catalog.get(entry.game_id))
+        .map(|game| game.name)
+        .collect();
+    let count = names.len();
+    let listed = summary(lang, names.into_iter());
+    ctx.send(|builder| {
+        builder.embed(|e| {
+            e.title(tr_args(lang, "watch.title", &[("count", &count)]))
+            .description(listed)
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// Removes a game from your watchlist
+#[poise::command(prefix_command, slash_command)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[description = "Game to stop watching"] game: String,
+) -> CommandResult {
+    let data: &Data = ctx.data();
+    let lang: Lang = lang(ctx);
+    let Some(found) = data.0.catalog.resolve(&game) else {
+        ctx.say(tr_args(lang, "watch.not_watched", &[("game", &game)])).await?;
+        return Ok(());
+    };
+    if data.0.watchlists.remove(ctx.author().id, found.id).await? {
+        ctx.say(tr_args(lang, "watch.removed", &[("game", &found.name)])).await?;
+    } else {
+        ctx.say(tr_args(lang, "watch.not_watched", &[("game", &found.name)])).await?;
+    }
+    Ok(())
+}
+
+/// Adds the games of a public Steam wishlist to your watchlist
+#[poise::command(prefix_command, slash_command, rename = "import-steam")]
+pub async fn import_steam(
+    ctx: Context<'_>,
+    #[description = "Steam profile link, custom URL name or SteamID64"] profile: String,
+) -> CommandResult {
+    if !cooldown::allow(ctx).await? {
+        return Ok(());
+    }
+    ctx.defer().await?;
+    let data: &Data = ctx.data();
+    let fetcher: &Fetcher = &data.0.fetcher;
+    let lang: Lang = lang(ctx);
+
+    let Some(steam_id) = steam_id(fetcher, &profile).await? else {
+        ctx.say(tr_args(lang, "watch.profile_not_found", &[("profile", &profile)])).await?;
+        return Ok(());
+    };
+    let app_ids = wishlist(fetcher, &steam_id).await?;
+    if app_ids.is_empty() {
+        ctx.say(tr(lang, "watch.private")).await?;
+        return Ok(());
+    }
+    info!("Importing {} wishlisted apps of {}", app_ids.len(), steam_id);
+
+    // Every wishlisted app that is a game the store sells becomes a catalog game
+    let mut found: Vec<(String, [(Store, Game); 1])> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    for item in store_items(fetcher, lang, data.region(ctx.guild_id()), &app_ids).await? {
+        match item {
+            Ok(game) => found.push((game.name.clone(), [(Store::Steam, game)])),
+            Err(reason) => skipped.push(reason),
+        }
+    }
+    let searches: Vec<(&str, &str, &[(Store, Game)])> = found.iter()
+        .map(|(name, matches)| (name.as_str(), name.as_str(), matches.as_slice()))
+        .collect();
+    let games: Vec<CatalogGame> = data.0.catalog.record_many(&searches).await?;
+
+    let added = data.0.watchlists.add(ctx.author().id, &games.iter().map(|game| game.id).collect::<Vec<_>>()).await?;
+    let (added, already): (Vec<&CatalogGame>, Vec<&CatalogGame>) = games.iter().partition(|game| added.contains(&game.id));
+
+    let mut fields: Vec<(String, String, bool)> = Vec::new();
+    fields.push((tr_args(lang, "watch.import_added", &[("count", &added.len())]), summary(lang, added.iter().map(|game| game.name.clone())), false));
+    if !already.is_empty() {
+        fields.push((tr_args(lang, "watch.import_already", &[("count", &already.len())]), summary(lang, already.iter().map(|game| game.name.clone())), false));
+    }
+    if !skipped.is_empty() {
+        fields.push((tr_args(lang, "watch.import_skipped", &[("count", &skipped.len())]), summary(lang, skipped.into_iter()), false));
+    }
+
+    ctx.send(|builder| {
+        builder.embed(|e| {
+            e.title(tr(lang, "watch.import_title"))
+            .description(tr_args(lang, "watch.import_read", &[("count", &app_ids.len())]))
+            .fields(fields)
+        })
+    })
+    .await?;
+    Ok(())
+}
+
+/// A watchlist game by any of its names, games the catalog does not know yet
+/// are looked up on Steam.
+async fn find_game(data: &Data, region: Region, game: &str) -> Result<Option<CatalogGame>, Error> {
+    if let Some(known) = data.0.catalog.resolve(game) {
+        return Ok(Some(known));
+    }
+    let cached = cached_scrape(data, Store::Steam, region, game).await?;
+    let games: GamesVec = GamesVec { games: cached.scrape.games.clone() };
+    match search_in(&games, game).game {
+        Some(found) => {
+            let name = found.name.clone();
+            Ok(Some(data.0.catalog.record(&name, game, &[(Store::Steam, found)]).await?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Lists names in a field, summing up those past the first few.
+fn summary(lang: Lang, names: impl Iterator<Item = String>) -> String {
+    let names: Vec<String> = names.collect();
+    let mut text = names.iter().take(LISTED).cloned().collect::<Vec<_>>().join("\n");
+    if names.len() > LISTED {
+        text.push('\n');
+        text.push_str(&tr_args(lang, "watch.more", &[("count", &(names.len() - LISTED))]));
+    }
+    if text.is_empty() { tr(lang, "watch.none") } else { text }
+}
+
+/// Reads the SteamID64 out of a profile link, a custom URL name or the id itself.
+async fn steam_id(fetcher: &Fetcher, profile: &str) -> Result<Option<String>, Error> {
+	// API endpoint var
+    const STEAM_COMMUNITY_URL: &str = "https://steamcommunity.com/id/";
+
+    let profile = profile.trim().trim_end_matches('/');
+    let last = profile.rsplit('/').next().unwrap_or(profile);
+    if profile.contains("/profiles/") || (last.len() == 17 && last.chars().all(|c| c.is_ascii_digit())) {
+        return Ok(Some(last.to_string()));
+    }
+
+    // Custom URLs are resolved through the profile's XML
+    let vanity: String = byte_serialize(last.as_bytes()).collect();
+    let url: String = format!("{}{}/?xml=1", &STEAM_COMMUNITY_URL, &vanity);
+
+    info!("STEAM_COMMUNITY_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    Ok(response_str.split_once("<steamID64>")
+        .and_then(|(_, rest)| rest.split_once("</steamID64>"))
+        .map(|(id, _)| id.trim().to_string()))
+}
+
+/// The app ids on a public wishlist, empty when the wishlist is private.
+async fn wishlist(fetcher: &Fetcher, steam_id: &str) -> Result<Vec<u64>, Error> {
+	// API endpoint var
+    const STEAM_WISHLIST_URL: &str = "https://api.steampowered.com/IWishlistService/GetWishlist/v1/?steamid=";
+
+    let url: String = format!("{}{}", &STEAM_WISHLIST_URL, steam_id);
+
+    info!("STEAM_WISHLIST_URL call: {:#?}", url);
+
+    let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+    let response: Value = serde_json::from_str(&response_str)?;
+    Ok(response["response"]["items"].as_array().into_iter().flatten()
+        .filter_map(|item| item["appid"].as_u64())
+        .collect())
+}
+
+/// Looks the apps up in the Steam store, a game it sells or why it was skipped.
+async fn store_items(fetcher: &Fetcher, lang: Lang, region: Region, app_ids: &[u64]) -> Result<Vec<Result<Game, String>>, Error> {
+	// API endpoint var
+    const STEAM_ITEMS_URL: &str = "https://api.steampowered.com/IStoreBrowseService/GetItems/v1/?input_json=";
+    const STEAM_APP_URL: &str = "https://store.steampowered.com/app/";
+
+    let mut items: Vec<Result<Game, String>> = Vec::new();
+    for chunk in app_ids.chunks(100) {
+        let input = json!({
+            "ids": chunk.iter().map(|id| json!({ "appid": id })).collect::<Vec<_>>(),
+            "context": { "language": "english", "country_code": region.country() },
+            "data_request": { "include_basic_info": true },
+        });
+        let input_encoded: String = byte_serialize(input.to_string().as_bytes()).collect();
+        let url: String = format!("{}{}", &STEAM_ITEMS_URL, &input_encoded);
+
+        info!("STEAM_ITEMS_URL call for {} apps", chunk.len());
+
+        let response_str: String = fetcher.fetch_api(&url, HeaderMap::new()).await?;
+        let response: Value = serde_json::from_str(&response_str)?;
+        let found: Vec<&Value> = response["response"]["store_items"].as_array().into_iter().flatten().collect();
+
+        for app_id in chunk {
+            let item = found.iter().find(|item| item["appid"].as_u64() == Some(*app_id));
+            let name = item.and_then(|item| item["name"].as_str()).unwrap_or_default();
+            let label = if name.is_empty() { format!("App {}", app_id) } else { name.to_string() };
+            match item {
+                None => items.push(Err(tr_args(lang, "watch.skip_not_found", &[("app", &label)]))),
+                Some(item) if item["success"].as_u64() != Some(1) || item["visible"] == false => {
+                    items.push(Err(tr_args(lang, "watch.skip_not_sold", &[("app", &label), ("country", &region.country())])))
+                }
+                // Type 0 is a game, the others are DLCs, software, soundtracks...
+                Some(item) if item["type"].as_u64().unwrap_or(0) != 0 => {
+                    items.push(Err(tr_args(lang, "watch.skip_not_game", &[("app", &label)])))
+                }
+                Some(_) => items.push(Ok(Game {
+                    site: "Steam".to_string(),
+                    product_id: app_id.to_string(),
+                    name: name.to_string(),
+                    url: format!("{}{}", STEAM_APP_URL, app_id),
+                    ..Default::default()
+                })),
+            }
+        }
+    }
+
+    let skipped = items.iter().filter(|item| item.is_err()).count();
+    if skipped > 0 {
+        warn!("{} of {} wishlisted apps skipped", skipped, app_ids.len());
+    }
+    Ok(items)
+}
+
+pub fn commands() -> [Command; 1] {
+    [watch()]
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The settings the bot starts with, by the names listed in the README.
+///
+/// On Shuttle they are its secrets, on its own the bot reads them from the
+/// environment and then from a TOML file laid out like `Secrets.toml`.
+pub struct Config {
+    lookup: Box<dyn Fn(&str) -> Option<String>>,
+}
+
+impl Config {
+    pub fn new(lookup: impl Fn(&str) -> Option<String> + 'static) -> Config {
+        Config { lookup: Box::new(lookup) }
+    }
+
+    /// Environment variables first, then the file when there is one.
+    pub fn from_env_and_file(path: Option<&Path>) -> anyhow::Result<Config> {
+        let file: HashMap<String, String> = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow::anyhow!("Could not read {:?}: {}", path, e))?;
+                let table: toml::Table = toml::from_str(&text)
+                    .map_err(|e| anyhow::anyhow!("Could not parse {:?}: {}", path, e))?;
+                // Numbers and flags may be written bare, they are read back from text anyway
+                table.into_iter()
+                    .map(|(key, value)| match value {
+                        toml::Value::String(text) => (key, text),
+                        other => (key, other.to_string()),
+                    })
+                    .collect()
+            }
+            None => HashMap::new(),
+        };
+        Ok(Config::new(move |key| std::env::var(key).ok().or_else(|| file.get(key).cloned())))
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        (self.lookup)(key)
+    }
+}
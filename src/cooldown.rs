@@ -0,0 +1,109 @@
+use chrono::Utc;
+use poise::serenity_prelude as serenity;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::i18n::{lang, tr_args, Lang};
+use crate::structs::{Context, Error};
+
+/// Who a cooldown applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Scope {
+    User(u64),
+    Guild(u64),
+    Global,
+}
+
+/// How often an expensive command may reach the stores, a zero duration
+/// turns that cooldown off.
+#[derive(Clone, Copy, Debug)]
+pub struct CooldownConfig {
+    pub user: Duration,
+    pub guild: Duration,
+    pub global: Duration,
+}
+
+/// When each user, guild and the whole bot last used each expensive command.
+pub struct Cooldowns {
+    config: CooldownConfig,
+    last_use: Mutex<HashMap<(String, Scope), Instant>>,
+}
+
+impl Cooldowns {
+    pub fn new(config: CooldownConfig) -> Cooldowns {
+        Cooldowns { config, last_use: Mutex::new(HashMap::new()) }
+    }
+
+    /// Records a use of the command, or tells how long until it may be used
+    /// again when any of its cooldowns is still running.
+    fn try_use(&self, command: &str, user_id: serenity::UserId, guild_id: Option<serenity::GuildId>) -> Result<(), Duration> {
+        let now = Instant::now();
+        let scopes: Vec<(Scope, Duration)> = [
+            Some((Scope::User(user_id.0), self.config.user)),
+            guild_id.map(|guild_id| (Scope::Guild(guild_id.0), self.config.guild)),
+            Some((Scope::Global, self.config.global)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|(_, cooldown)| !cooldown.is_zero())
+        .collect();
+
+        let mut last_use = self.last_use.lock().unwrap();
+        let longest = self.config.user.max(self.config.guild).max(self.config.global);
+        last_use.retain(|_, used_at| now.duration_since(*used_at) < longest);
+
+        let wait = scopes.iter()
+            .filter_map(|(scope, cooldown)| {
+                let used_at = last_use.get(&(command.to_string(), *scope))?;
+                cooldown.checked_sub(now.duration_since(*used_at))
+            })
+            .max()
+            .unwrap_or(Duration::ZERO);
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+        for (scope, _) in scopes {
+            last_use.insert((command.to_string(), scope), now);
+        }
+        Ok(())
+    }
+}
+
+/// Lets an expensive command go on, or tells the user when they may retry.
+pub async fn allow(ctx: Context<'_>) -> Result<bool, Error> {
+    let command = &ctx.command().qualified_name;
+    let Err(wait) = ctx.data().0.cooldowns.try_use(command, ctx.author().id, ctx.guild_id()) else {
+        return Ok(true);
+    };
+    info!("{} on cooldown for {} during {:?}", command, ctx.author().id, wait);
+
+    let reply = wait_text(lang(ctx), command, wait);
+    ctx.send(|builder| builder.content(reply).ephemeral(true)).await?;
+    Ok(false)
+}
+
+/// Same as `allow` for a button of the command that reaches the stores, the
+/// cooldowns are those of whoever pressed it.
+pub async fn allow_press(ctx: Context<'_>, press: &serenity::MessageComponentInteraction) -> Result<bool, Error> {
+    let command = &ctx.command().qualified_name;
+    let Err(wait) = ctx.data().0.cooldowns.try_use(command, press.user.id, press.guild_id) else {
+        return Ok(true);
+    };
+    info!("{} button on cooldown for {} during {:?}", command, press.user.id, wait);
+
+    let reply = wait_text(lang(ctx), command, wait);
+    press.create_interaction_response(ctx.serenity_context(), |r| {
+        r.kind(serenity::InteractionResponseType::ChannelMessageWithSource)
+        .interaction_response_data(|d| d.content(reply).ephemeral(true))
+    })
+    .await?;
+    Ok(false)
+}
+
+fn wait_text(lang: Lang, command: &str, wait: Duration) -> String {
+    // Rounded up so the retry time shown is never early
+    let retry_at = Utc::now().timestamp() + wait.as_secs() as i64 + 1;
+    tr_args(lang, "cooldown.wait", &[("command", &command), ("when", &format!("<t:{}:R>", retry_at))])
+}
@@ -0,0 +1,287 @@
+use headless_chrome::{Browser, LaunchOptions, Tab};
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::http::{header_map, response_body, FetchError, HttpClient, USER_AGENT};
+use crate::ratelimit::RateLimiter;
+use crate::recorder::{Capture, RecordedRequest, RecordedResponse};
+use crate::robots::{Robots, RobotsCache};
+use crate::selectors::StoreSelectors;
+use crate::structs::Error;
+
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<String, Error>> + Send + 'a>>;
+
+/// How a store's pages are retrieved, set per store in `selectors.toml`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// A plain GET, enough for server rendered pages.
+    #[default]
+    Http,
+    /// A headless Chrome tab, for storefronts rendered client side.
+    Browser,
+}
+
+pub struct FetchRequest {
+    pub url: String,
+    pub headers: HeaderMap,
+    /// Selector that must be present before the page counts as loaded.
+    pub wait_for: String,
+    pub timeout: Option<Duration>,
+}
+
+/// Something that can turn a request into the page's HTML.
+pub trait Fetch: Send + Sync {
+    fn fetch<'a>(&'a self, request: &'a FetchRequest) -> FetchFuture<'a>;
+}
+
+impl Fetch for HttpClient {
+    fn fetch<'a>(&'a self, request: &'a FetchRequest) -> FetchFuture<'a> {
+        Box::pin(self.get_text(&request.url, request.headers.clone()))
+    }
+}
+
+/// A bounded set of headless Chrome instances shared by every browser fetch.
+///
+/// Browsers are launched lazily, handed out one fetch at a time and put back
+/// afterwards, unless they failed in which case a fresh one replaces them.
+pub struct BrowserPool {
+    capture: Arc<Capture>,
+    idle: Mutex<Vec<Browser>>,
+    permits: Semaphore,
+    page_timeout: Duration,
+}
+
+impl BrowserPool {
+    pub fn new(size: usize, page_timeout: Duration, capture: Arc<Capture>) -> BrowserPool {
+        BrowserPool {
+            capture,
+            idle: Mutex::new(Vec::new()),
+            permits: Semaphore::new(size.max(1)),
+            page_timeout,
+        }
+    }
+
+    async fn render(&self, request: &FetchRequest) -> Result<RecordedResponse, Error> {
+        let _permit = self.permits.acquire().await?;
+        let browser = self.idle.lock().unwrap().pop();
+
+        let url = request.url.clone();
+        let headers = header_map(&request.headers);
+        let wait_for = request.wait_for.clone();
+        let timeout = request.timeout.unwrap_or(self.page_timeout);
+
+        let (browser, page) = tokio::task::spawn_blocking(move || {
+            let browser = match browser {
+                Some(browser) => browser,
+                None => match launch_browser() {
+                    Ok(browser) => browser,
+                    Err(e) => return (None, Err(e)),
+                },
+            };
+            // A browser that cannot open tabs anymore is dropped, a page
+            // that failed to load says nothing about the browser itself
+            let tab = match browser.new_tab() {
+                Ok(tab) => tab,
+                Err(e) => return (None, Err(e.into())),
+            };
+            (Some(browser), render_page(&tab, &url, &headers, &wait_for, timeout))
+        })
+        .await?;
+
+        if let Some(browser) = browser {
+            self.idle.lock().unwrap().push(browser);
+        }
+
+        // The browser does not expose the response headers, a throttled
+        // store gets the default pause rather than its `Retry-After`
+        let (status, body) = page?;
+        Ok(RecordedResponse { status, headers: BTreeMap::new(), body })
+    }
+}
+
+impl Fetch for BrowserPool {
+    fn fetch<'a>(&'a self, request: &'a FetchRequest) -> FetchFuture<'a> {
+        Box::pin(async move {
+            let recorded = RecordedRequest {
+                method: "BROWSER".to_string(),
+                url: request.url.clone(),
+                headers: header_map(&request.headers),
+            };
+            let response = self.capture.exchange(recorded, self.render(request)).await?;
+            response_body(&request.url, response)
+        })
+    }
+}
+
+fn launch_browser() -> Result<Browser, Error> {
+    info!("Launching headless browser");
+    let options = LaunchOptions {
+        headless: true,
+        // Pooled browsers sit idle between searches, keep them alive
+        idle_browser_timeout: Duration::from_secs(60 * 60),
+        ..Default::default()
+    };
+    Ok(Browser::new(options)?)
+}
+
+fn render_page(
+    tab: &Tab,
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    wait_for: &str,
+    timeout: Duration,
+) -> Result<(u16, String), Error> {
+    tab.set_default_timeout(timeout);
+
+    let page = (|| {
+        tab.set_user_agent(USER_AGENT, None, None)?;
+        if !headers.is_empty() {
+            let headers: HashMap<&str, &str> = headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            tab.set_extra_http_headers(headers)?;
+        }
+        tab.navigate_to(url)?.wait_until_navigated()?;
+        // An error page never shows the results, no use waiting for them
+        let status = navigation_status(tab);
+        if (200..=299).contains(&status) {
+            tab.wait_for_element_with_custom_timeout(wait_for, timeout)?;
+        }
+        Ok::<(u16, String), anyhow::Error>((status, tab.get_content()?))
+    })();
+
+    if let Err(e) = tab.close(true) {
+        warn!("Failed to close browser tab: {:?}", e);
+    }
+    Ok(page?)
+}
+
+/// The status the store answered the page with, read from the page itself.
+/// Browsers too old to tell count as a success.
+fn navigation_status(tab: &Tab) -> u16 {
+    tab.evaluate("performance.getEntriesByType('navigation')[0]?.responseStatus ?? 0", false)
+        .ok()
+        .and_then(|result| result.value)
+        .and_then(|value| value.as_u64())
+        .filter(|status| *status > 0)
+        .map_or(200, |status| status as u16)
+}
+
+/// How long to stay away from a store that throttled without `Retry-After`.
+const DEFAULT_THROTTLE_PAUSE: Duration = Duration::from_secs(60);
+
+/// Picks the backend configured for each store and keeps the bot polite:
+/// requests are rate limited per host, robots.txt is honored and a store
+/// asking to slow down is left alone for as long as it asked.
+#[derive(Clone)]
+pub struct Fetcher {
+    http: HttpClient,
+    browser: Arc<BrowserPool>,
+    limiter: Arc<RateLimiter>,
+    robots: Arc<RobotsCache>,
+    respect_robots: bool,
+}
+
+impl Fetcher {
+    pub fn new(http: HttpClient, browser: BrowserPool, limiter: RateLimiter, respect_robots: bool) -> Fetcher {
+        Fetcher {
+            http,
+            browser: Arc::new(browser),
+            limiter: Arc::new(limiter),
+            robots: Arc::new(RobotsCache::default()),
+            respect_robots,
+        }
+    }
+
+    pub async fn fetch(&self, selectors: &StoreSelectors, url: &str, headers: HeaderMap) -> Result<String, Error> {
+        let request = FetchRequest {
+            url: url.to_string(),
+            headers,
+            wait_for: selectors.wait_for.clone(),
+            timeout: selectors.page_timeout,
+        };
+        let backend: &dyn Fetch = match selectors.backend {
+            Backend::Http => &self.http,
+            Backend::Browser => self.browser.as_ref(),
+        };
+
+        // Replays never reach the stores, there is nobody to be polite to
+        if self.http.is_replaying() {
+            return backend.fetch(&request).await;
+        }
+
+        let parsed = Url::parse(url)?;
+        let host = parsed.host_str().unwrap_or_default().to_string();
+        self.check_robots(&parsed, selectors.requests_per_minute).await?;
+
+        self.limiter.acquire(&host, selectors.requests_per_minute).await;
+        let result = backend.fetch(&request).await;
+        self.pause_if_throttled(&host, &result);
+        result
+    }
+
+    /// Fetches a store API over plain HTTP, with the same rate limiting and
+    /// robots.txt rules as page fetches.
+    pub async fn fetch_api(&self, url: &str, headers: HeaderMap) -> Result<String, Error> {
+        if self.http.is_replaying() {
+            return self.http.get_text(url, headers).await;
+        }
+
+        let parsed = Url::parse(url)?;
+        let host = parsed.host_str().unwrap_or_default().to_string();
+        self.check_robots(&parsed, None).await?;
+
+        self.limiter.acquire(&host, None).await;
+        let result = self.http.get_text(url, headers).await;
+        self.pause_if_throttled(&host, &result);
+        result
+    }
+
+    fn pause_if_throttled(&self, host: &str, result: &Result<String, Error>) {
+        if let Err(e) = result {
+            if let Some(FetchError::Throttled { retry_after, .. }) = e.downcast_ref::<FetchError>() {
+                self.limiter.pause(host, retry_after.unwrap_or(DEFAULT_THROTTLE_PAUSE));
+            }
+        }
+    }
+
+    async fn check_robots(&self, url: &Url, per_minute: Option<u32>) -> Result<(), Error> {
+        let origin = url.origin().ascii_serialization();
+        let robots = match self.robots.get(&origin) {
+            Some(robots) => robots,
+            None => {
+                let host = url.host_str().unwrap_or_default();
+                self.limiter.acquire(host, per_minute).await;
+                // A missing or unreadable robots.txt allows everything
+                let robots = match self.http.get_text(&format!("{}/robots.txt", origin), HeaderMap::new()).await {
+                    Ok(text) => Robots::parse(&text),
+                    Err(e) => {
+                        info!("No robots.txt for {}: {}", origin, e);
+                        Robots::default()
+                    }
+                };
+                self.robots.insert(&origin, robots)
+            }
+        };
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if robots.allows(&path) {
+            return Ok(());
+        }
+        if self.respect_robots {
+            return Err(FetchError::Disallowed { url: url.to_string() }.into());
+        }
+        warn!("{} is disallowed by robots.txt, fetching anyway", url);
+        Ok(())
+    }
+}
@@ -1,11 +1,10 @@
-use scraper::Selector;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use scraper::{ElementRef, Selector};
+use std::path::Path;
+use url::Url;
 
 use crate::structs::{Game, GameOpt, GamesVec};
 
-pub fn make_selector(selector: &str) -> Selector {
-    Selector::parse(selector).unwrap()
-}
-
 pub fn get_element_text(select: &scraper::element_ref::Select) -> String {
     let mut elements: Vec<String> = Vec::new();
     for e in select.clone() {
@@ -34,9 +33,178 @@ pub fn get_attr_text(select: &mut scraper::element_ref::Select, attr: &str) -> S
     attr_value
 }
 
+pub fn select_text(row: &ElementRef, selector: &Option<Selector>) -> String {
+    match selector {
+        Some(selector) => get_element_text(&row.select(selector)),
+        None => String::new(),
+    }
+}
+
+pub fn select_currency(row: &ElementRef, selector: &Option<Selector>) -> String {
+    match selector {
+        Some(selector) => get_currency(&row.select(selector)),
+        None => String::new(),
+    }
+}
+
+/// Reads `attr` from the element matched by `selector`, or from the row
+/// itself when there is no selector.
+pub fn select_attr(row: &ElementRef, selector: &Option<Selector>, attr: &str) -> String {
+    match selector {
+        Some(selector) => get_attr_text(&mut row.select(selector), attr),
+        None => row.value().attr(attr).unwrap_or_default().to_string(),
+    }
+}
+
+/// Reads the product link of a row, resolved against the store's url.
+pub fn select_link(row: &ElementRef, selector: &Option<Selector>, attr: &Option<String>, base: &str) -> String {
+    let href = match attr {
+        Some(attr) => select_attr(row, selector, attr),
+        None => return String::new(),
+    };
+    match Url::parse(base).and_then(|base| base.join(&href)) {
+        Ok(url) if !href.is_empty() => url.to_string(),
+        _ => href,
+    }
+}
+
+/// The symbol shown before prices in a currency, the code itself otherwise.
+pub fn currency_symbol(currency: &str) -> &str {
+    match currency {
+        "BRL" => "R$",
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        other => other,
+    }
+}
+
+/// Formats a price from a store API the way scraped prices read.
+pub fn format_price(amount: f64, currency: &str) -> String {
+    format!("{} {:.2}", currency_symbol(currency), amount)
+}
+
+pub fn discount_pct(full_price: f64, price: f64) -> String {
+    if full_price <= 0.0 || price >= full_price {
+        return "0%".to_string();
+    }
+    format!("-{:.0}%", (1.0 - price / full_price) * 100.0)
+}
+
+/// The percentage of a discount as stores list it, as in "-35%" or "35% off".
+pub fn parse_discount(discount: &str) -> u8 {
+    let whole = discount.split(['.', ',']).next().unwrap_or_default();
+    whole.chars().filter(|c| c.is_ascii_digit()).collect::<String>().parse::<u8>().unwrap_or(0).min(100)
+}
+
+/// Reads a sale or bundle end date, either RFC 3339 or a UTC `YYYY-MM-DD HH:MM:SS`.
+pub fn parse_end_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text).map(|d| d.with_timezone(&Utc)).ok()
+        .or_else(|| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").ok().map(|d| d.and_utc()))
+}
+
+/// Regions key marketplaces lock their keys to, as written at the end of a listing.
+const KEY_REGIONS: [&str; 10] = [
+    "GLOBAL", "ROW", "EUROPE", "EU", "LATAM", "NORTH AMERICA", "UNITED STATES", "US", "BRAZIL", "BR",
+];
+
+/// What is appended to a game's name in key listings.
+const KEY_SUFFIXES: [&str; 12] = [
+    "Key", "Gift", "Account", "(PC)", "PC", "Steam", "GOG.COM", "GOG", "Epic Games", "EA App", "Ubisoft Connect", "Battle.net",
+];
+
+/// Splits a key listing like "Hades (PC) Steam Key GLOBAL" into the game's
+/// name and the region the key is locked to.
+pub fn split_key_listing(listing: &str) -> (String, String) {
+    let mut name = listing.trim();
+    let mut region = String::new();
+    for key_region in KEY_REGIONS {
+        if let Some(rest) = name.strip_suffix(key_region).filter(|rest| rest.ends_with(' ')) {
+            region = key_region.to_string();
+            name = rest.trim_end();
+            break;
+        }
+    }
+    while let Some(rest) = KEY_SUFFIXES.iter()
+        .find_map(|suffix| name.strip_suffix(suffix).filter(|rest| rest.ends_with(' ')))
+    {
+        name = rest.trim_end();
+    }
+    (name.to_string(), region)
+}
+
+/// Words that make a retail listing a console disc or code rather than a PC key.
+const CONSOLES: [&str; 7] = ["PlayStation", "PS4", "PS5", "Xbox", "Nintendo", "Switch", "Wii"];
+
+/// The game's name in a retailer listing like "Jogo Hades, PC, Steam - Digital
+/// Download" or "Hades - Código Digital", `None` when it is for a console.
+pub fn retail_game_name(listing: &str) -> Option<String> {
+    let listing = listing.trim();
+    if listing.split(|c: char| !c.is_alphanumeric()).any(|word| CONSOLES.contains(&word)) {
+        return None;
+    }
+    // What follows the first comma, or else the last dash, is the platform and the edition
+    let name = match listing.split_once(", ") {
+        Some((name, _)) => name,
+        None => listing.rsplit_once(" - ").map_or(listing, |(name, _)| name),
+    };
+    let name = name.strip_prefix("Jogo ").unwrap_or(name).trim();
+    if name.is_empty() { None } else { Some(name.to_string()) }
+}
+
+/// A title as compared across stores: lowercase, without trademark signs
+/// and punctuation, so "The Witcher® 3: Wild Hunt" and "the witcher 3 wild
+/// hunt" are the same game.
+pub fn normalize_title(title: &str) -> String {
+    title.chars()
+        .filter(|c| !matches!(c, '™' | '®' | '©'))
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What is written after a game's name for editions other than the standard one.
+const EDITIONS: [&str; 9] = [
+    "Game of the Year Edition", "GOTY Edition", "GOTY", "Deluxe Edition", "Gold Edition", "Ultimate Edition",
+    "Complete Edition", "Definitive Edition", "Collector's Edition",
+];
+
+/// The edition a listing names, empty for the standard edition.
+pub fn edition_of(name: &str) -> String {
+    let lowercase = name.to_lowercase();
+    EDITIONS.iter()
+        .find(|edition| lowercase.contains(&edition.to_lowercase()))
+        .map(|edition| edition.to_string())
+        .unwrap_or_default()
+}
+
+/// Writes a file aside then renames it over the old one, so a crash never
+/// leaves it truncated.
+pub async fn write_file_atomic(path: &Path, contents: String) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, contents).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+/// Lowercases a search and collapses its whitespace, so equivalent searches
+/// share a cache entry.
+pub fn normalize_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub fn search_in(games_searched: &GamesVec, text: &str) -> GameOpt {
     let index = games_searched.games.iter().position(|game| {
-        game.name.to_lowercase() == text.to_lowercase()
+        normalize_title(&game.name) == normalize_title(text)
     });
 
     let game_matched: Option<Game> = match index {
@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude as serenity;
+use std::fmt::Display;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::commands::scraper::scrape;
+use crate::funcs::search_in;
+use crate::i18n::{tr, tr_args, Lang};
+use crate::structs::{Data, GamesVec, Scrape, Store};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreHealth {
+    /// The canary search returned priced results.
+    Healthy,
+    /// The page looked as expected but listed nothing.
+    NoResults,
+    /// The page did not match the selectors, the reason says which part.
+    StructureChanged(String),
+    /// The store could not be reached at all.
+    Unreachable(String),
+}
+
+impl StoreHealth {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, StoreHealth::Healthy)
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            StoreHealth::Healthy => "🟢",
+            StoreHealth::NoResults => "🟡",
+            StoreHealth::StructureChanged(_) | StoreHealth::Unreachable(_) => "🔴",
+        }
+    }
+
+    /// Tells apart an empty search from a page whose structure changed.
+    pub fn from_scrape(scrape: &Scrape, canary: &str) -> StoreHealth {
+        if scrape.containers == 0 {
+            return StoreHealth::StructureChanged("results container missing".to_string());
+        }
+        if scrape.rows == 0 {
+            return StoreHealth::NoResults;
+        }
+        if scrape.games.is_empty() {
+            return StoreHealth::StructureChanged(format!("{} rows found but no prices parsed", scrape.rows));
+        }
+        if scrape.games.iter().all(|game| game.name.is_empty()) {
+            return StoreHealth::StructureChanged(format!("{} priced rows found but no titles parsed", scrape.games.len()));
+        }
+        let games = GamesVec { games: scrape.games.clone() };
+        if search_in(&games, canary).game.is_none() {
+            return StoreHealth::NoResults;
+        }
+        StoreHealth::Healthy
+    }
+
+    /// The health as told to users, the reasons stay as the checks wrote them.
+    pub fn text(&self, lang: Lang) -> String {
+        match self {
+            StoreHealth::Healthy => tr(lang, "status.healthy"),
+            StoreHealth::NoResults => tr(lang, "status.no_results"),
+            StoreHealth::StructureChanged(reason) => tr_args(lang, "status.structure_changed", &[("reason", reason)]),
+            StoreHealth::Unreachable(reason) => tr_args(lang, "status.unreachable", &[("reason", reason)]),
+        }
+    }
+}
+
+impl Display for StoreHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreHealth::Healthy => write!(f, "Healthy"),
+            StoreHealth::NoResults => write!(f, "No results for the canary title"),
+            StoreHealth::StructureChanged(reason) => write!(f, "Page structure changed: {}", reason),
+            StoreHealth::Unreachable(reason) => write!(f, "Unreachable: {}", reason),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    pub store: Store,
+    pub health: StoreHealth,
+    pub checked_at: DateTime<Utc>,
+}
+
+pub async fn check_store(data: &Data, store: Store) -> HealthReport {
+    let fetcher = data.0.fetcher.clone();
+    let selectors = data.selectors();
+    let health = match scrape(store, fetcher, &selectors, data.0.region, store.canary().to_string()).await {
+        Ok(scrape) => StoreHealth::from_scrape(&scrape, store.canary()),
+        Err(e) => StoreHealth::Unreachable(e.to_string()),
+    };
+
+    info!("Health check for {}: {}", store, health);
+    HealthReport { store, health, checked_at: Utc::now() }
+}
+
+/// Checks every store, remembers the results and returns them along with
+/// the reports whose health changed since the previous check.
+pub async fn check_all(data: &Data) -> (Vec<HealthReport>, Vec<HealthReport>) {
+    let mut reports: Vec<HealthReport> = Vec::new();
+    for store in Store::ALL.into_iter().filter(|store| store.sells_in(data.0.region)) {
+        // Key resellers are only searched for guilds that opted in
+        if store.is_reseller() && !data.0.settings.any(|settings| settings.searches(store)) {
+            continue;
+        }
+        reports.push(check_store(data, store).await);
+    }
+
+    let mut changed: Vec<HealthReport> = Vec::new();
+    let mut last_reports = data.0.health.lock().unwrap();
+    for report in &reports {
+        let previous = last_reports.insert(report.store, report.clone());
+        let was_healthy = previous.map(|p| p.health.is_healthy()).unwrap_or(true);
+        if was_healthy != report.health.is_healthy() {
+            changed.push(report.clone());
+        }
+    }
+
+    (reports, changed)
+}
+
+pub async fn alert(ctx: &serenity::Context, channel: serenity::ChannelId, changed: &[HealthReport]) {
+    for report in changed {
+        let message = if report.health.is_healthy() {
+            format!("{} **{}** scraper recovered.", report.health.emoji(), report.store)
+        } else {
+            format!("{} **{}** scraper is broken: {}", report.health.emoji(), report.store, report.health)
+        };
+        if let Err(e) = channel.say(&ctx.http, message).await {
+            error!("Failed to send health alert to {}: {:?}", channel, e);
+        }
+    }
+}
+
+/// Runs the canary searches on a schedule, alerting the admin channel when
+/// a store breaks or recovers.
+pub fn spawn_checks(ctx: serenity::Context, data: Data, interval: Duration, admin_channel: Option<serenity::ChannelId>) {
+    if admin_channel.is_none() {
+        warn!("'ADMIN_CHANNEL_ID' not set, scraper breakage will only be logged");
+    }
+
+    tokio::spawn(async move {
+        let mut timer = tokio::time::interval(interval);
+        loop {
+            timer.tick().await;
+            let (_, changed) = check_all(&data).await;
+            if let Some(channel) = admin_channel {
+                alert(&ctx, channel, &changed).await;
+            }
+        }
+    });
+}
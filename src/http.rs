@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::recorder::{Capture, RecordedRequest, RecordedResponse};
+use crate::structs::Error;
+
+/// Identifies the bot to the stores, with a link explaining what it does.
+pub const USER_AGENT: &str = concat!(
+    "DealFinderBot/", env!("CARGO_PKG_VERSION"), " (+https://github.com/kaianlima/deal-finder-bot)"
+);
+
+/// A store request that did not produce a usable page.
+#[derive(Debug)]
+pub enum FetchError {
+    /// The store asked the bot to slow down (429 or 503).
+    Throttled { url: String, status: u16, retry_after: Option<Duration> },
+    /// Any other unsuccessful status.
+    Status { url: String, status: u16 },
+    /// The store's robots.txt does not allow the path.
+    Disallowed { url: String },
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Throttled { url, status, retry_after } => match retry_after {
+                Some(wait) => write!(f, "{} answered {}, retry after {:?}", url, status, wait),
+                None => write!(f, "{} answered {}", url, status),
+            },
+            FetchError::Status { url, status } => write!(f, "{} answered {}", url, status),
+            FetchError::Disallowed { url } => write!(f, "{} is disallowed by robots.txt", url),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// The client every plain HTTP store request goes through.
+///
+/// Wraps the shared `reqwest::Client` so traffic can be recorded to disk or
+/// replayed from a previous capture, depending on the configured `HttpMode`.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    capture: Arc<Capture>,
+}
+
+impl HttpClient {
+    pub fn new(client: reqwest::Client, capture: Arc<Capture>) -> HttpClient {
+        HttpClient { client, capture }
+    }
+
+    pub async fn get_text(&self, url: &str, headers: HeaderMap) -> Result<String, Error> {
+        let request = RecordedRequest {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: header_map(&headers),
+        };
+
+        let live = async {
+            let response = self.client.get(url)
+                .headers(headers)
+                .send()
+                .await?;
+            let status = response.status().as_u16();
+            let response_headers = header_map(response.headers());
+            let body = response.text().await?;
+            Ok::<RecordedResponse, Error>(RecordedResponse { status, headers: response_headers, body })
+        };
+
+        let response = self.capture.exchange(request, live).await?;
+        response_body(url, response)
+    }
+
+    /// Whether responses come from a capture instead of the stores.
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.capture.as_ref(), Capture::Replay(_))
+    }
+}
+
+/// The body of a successful response, or why the store turned the request down.
+pub fn response_body(url: &str, response: RecordedResponse) -> Result<String, Error> {
+    match response.status {
+        200..=299 => Ok(response.body),
+        429 | 503 => Err(FetchError::Throttled {
+            url: url.to_string(),
+            status: response.status,
+            retry_after: response.headers.get("retry-after").and_then(|value| parse_retry_after(value)),
+        }.into()),
+        status => Err(FetchError::Status { url: url.to_string(), status }.into()),
+    }
+}
+
+/// Reads a `Retry-After` header, given either in seconds or as an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+pub fn header_map(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers.iter()
+        .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or_default().to_string()))
+        .collect()
+}
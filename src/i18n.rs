@@ -0,0 +1,149 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::structs::{Command, Context, Region};
+
+/// The languages the bot replies in, each with a catalog in `locales/`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, poise::ChoiceParameter)]
+pub enum Lang {
+    #[default]
+    #[name = "English"]
+    #[serde(rename = "en-US")]
+    EnUs,
+    #[name = "Português (Brasil)"]
+    #[serde(rename = "pt-BR")]
+    PtBr,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::EnUs, Lang::PtBr];
+
+    /// The Discord locale of the language.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::EnUs => "en-US",
+            Lang::PtBr => "pt-BR",
+        }
+    }
+
+    /// The name of the language in itself.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Lang::EnUs => "English",
+            Lang::PtBr => "Português (Brasil)",
+        }
+    }
+
+    /// The language of a Discord locale, `None` for locales without a catalog.
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        if locale.starts_with("pt") {
+            Some(Lang::PtBr)
+        } else if locale.starts_with("en") {
+            Some(Lang::EnUs)
+        } else {
+            None
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Lang::EnUs => include_str!("../locales/en-US.toml"),
+            Lang::PtBr => include_str!("../locales/pt-BR.toml"),
+        }
+    }
+}
+
+lazy_static! {
+    /// Every catalog flattened to dotted keys, as in `price.not_found`.
+    static ref CATALOGS: HashMap<Lang, HashMap<String, String>> = Lang::ALL.into_iter()
+        .map(|lang| {
+            let table: toml::Table = toml::from_str(lang.source())
+                .unwrap_or_else(|e| panic!("Invalid {} catalog: {}", lang.code(), e));
+            let mut entries: HashMap<String, String> = HashMap::new();
+            flatten("", &table, &mut entries);
+            (lang, entries)
+        })
+        .collect();
+}
+
+fn flatten(prefix: &str, table: &toml::Table, entries: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, entries),
+            toml::Value::String(text) => {
+                entries.insert(key, text.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The text of a key in the language's own catalog only.
+fn entry(lang: Lang, key: &str) -> Option<&'static str> {
+    CATALOGS.get(&lang).and_then(|catalog| catalog.get(key)).map(String::as_str)
+}
+
+/// The text of a key, in English when the language lacks it and the key
+/// itself when no catalog has it.
+pub fn tr(lang: Lang, key: &str) -> String {
+    entry(lang, key)
+        .or_else(|| entry(Lang::EnUs, key))
+        .unwrap_or(key)
+        .to_string()
+}
+
+/// The text of a key with its `{name}` placeholders filled in.
+pub fn tr_args(lang: Lang, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = tr(lang, key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+/// The language to reply in: the user's choice, then the language of their
+/// Discord client, then the guild default and at last the language of the
+/// market prices are searched in.
+pub fn lang(ctx: Context<'_>) -> Lang {
+    let data = ctx.data();
+    data.0.user_settings.get(Some(ctx.author().id)).language
+        .or_else(|| ctx.locale().and_then(Lang::from_locale))
+        .or_else(|| data.0.settings.get(ctx.guild_id()).language)
+        .unwrap_or_else(|| region_lang(data.region(ctx.guild_id())))
+}
+
+pub fn region_lang(region: Region) -> Lang {
+    Lang::from_locale(region.locale()).unwrap_or_default()
+}
+
+/// Fills the per-locale names and descriptions Discord shows for the
+/// commands and their parameters from the `commands` section of each catalog.
+pub fn localize_commands(commands: &mut [Command]) {
+    for command in commands {
+        let prefix = format!("commands.{}", command.identifying_name);
+        localize_command(command, &prefix);
+    }
+}
+
+fn localize_command(command: &mut Command, prefix: &str) {
+    for lang in Lang::ALL {
+        if let Some(name) = entry(lang, &format!("{}.name", prefix)) {
+            command.name_localizations.insert(lang.code().to_string(), name.to_string());
+        }
+        if let Some(description) = entry(lang, &format!("{}.description", prefix)) {
+            command.description_localizations.insert(lang.code().to_string(), description.to_string());
+        }
+        for parameter in command.parameters.iter_mut() {
+            if let Some(description) = entry(lang, &format!("{}.params.{}", prefix, parameter.name)) {
+                parameter.description_localizations.insert(lang.code().to_string(), description.to_string());
+            }
+        }
+    }
+    for subcommand in command.subcommands.iter_mut() {
+        let prefix = format!("{}.{}", prefix, subcommand.identifying_name);
+        localize_command(subcommand, &prefix);
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::funcs::write_file_atomic;
+use crate::structs::Error;
+
+/// Data kept in memory and written to a JSON file on each change, so it
+/// survives restarts.
+pub struct JsonFile<T> {
+    path: PathBuf,
+    value: RwLock<T>,
+    /// Keeps concurrent changes from writing the file out of order.
+    write: tokio::sync::Mutex<()>,
+}
+
+impl<T: Default + Serialize + DeserializeOwned> JsonFile<T> {
+    /// Reads the file, a missing file means nothing was saved yet.
+    pub fn load(path: PathBuf) -> Result<JsonFile<T>, Error> {
+        let value: T = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Could not parse {:?}: {}", path, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => T::default(),
+            Err(e) => return Err(format!("Could not read {:?}: {}", path, e).into()),
+        };
+        Ok(JsonFile { path, value: RwLock::new(value), write: tokio::sync::Mutex::new(()) })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn read<R>(&self, read: impl FnOnce(&T) -> R) -> R {
+        read(&self.value.read().unwrap())
+    }
+
+    pub async fn update<R>(&self, change: impl FnOnce(&mut T) -> R) -> Result<R, Error> {
+        self.update_if(|value| (change(value), true)).await
+    }
+
+    /// Writes the file only when the change tells it changed something.
+    pub async fn update_if<R>(&self, change: impl FnOnce(&mut T) -> (R, bool)) -> Result<R, Error> {
+        let _write = self.write.lock().await;
+        let (result, json) = {
+            let mut value = self.value.write().unwrap();
+            let (result, changed) = change(&mut value);
+            let json = if changed { Some(serde_json::to_string_pretty(&*value)?) } else { None };
+            (result, json)
+        };
+        if let Some(json) = json {
+            write_file_atomic(&self.path, json).await?;
+        }
+        Ok(result)
+    }
+}
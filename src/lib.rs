@@ -0,0 +1,228 @@
+//! The bot itself, started by the Shuttle entry point or on its own.
+
+mod access;
+mod cache;
+mod catalog;
+mod circuit;
+mod commands;
+pub mod config;
+mod cooldown;
+mod fetcher;
+mod funcs;
+mod health;
+mod http;
+mod i18n;
+mod json_file;
+mod recorder;
+mod retry;
+mod ratelimit;
+mod robots;
+mod selectors;
+mod settings;
+mod structs;
+mod watchlist;
+
+use access::AccessControl;
+use anyhow::anyhow;
+use cache::SearchCache;
+use catalog::Catalog;
+use circuit::CircuitBreaker;
+use config::Config;
+use cooldown::{CooldownConfig, Cooldowns};
+use poise::serenity_prelude as serenity;
+use poise::serenity_prelude::GatewayIntents;
+use std::{path::PathBuf, sync::{Arc, RwLock}, time::Duration};
+use tracing::{error, info};
+use fetcher::{BrowserPool, Fetcher};
+use http::{HttpClient, USER_AGENT};
+use ratelimit::RateLimiter;
+use recorder::{Capture, HttpMode};
+use retry::RetryPolicy;
+use selectors::SelectorConfig;
+use settings::SettingsStore;
+use structs::{DataInner, Region};
+use watchlist::Watchlists;
+
+pub use structs::{Context, Data, Error};
+
+/// Sets the bot up from its configuration, the caller decides how it runs.
+pub fn framework(config: Config) -> anyhow::Result<poise::FrameworkBuilder<Data, Error>> {
+    // Get the discord token
+    let ds_token = if let Some(token) = config.get("DISCORD_TOKEN") {
+        token
+    } else {
+        return Err(anyhow!("'DISCORD_TOKEN' was not found"));
+    };
+
+    let discord_guild_id = if let Some(discord_guild_id) = config.get("DISCORD_GUILD_ID") {
+        discord_guild_id
+    } else {
+        return Err(anyhow!("'DISCORD_GUILD_ID' was not found"));
+    };
+
+    // Record or replay store traffic when asked to, see `HTTP_MODE` in the README
+    let http_mode = HttpMode::from_settings(config.get("HTTP_MODE"), config.get("HTTP_CAPTURE_DIR"))?;
+    info!("Store HTTP mode: {:?}", http_mode);
+    let capture = Arc::new(Capture::new(http_mode));
+    let reqwest = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| anyhow!("Could not build the HTTP client: {}", e))?;
+    let http = HttpClient::new(reqwest, capture.clone());
+
+    // Polite crawling: per host request rate and robots.txt
+    let requests_per_minute = match config.get("STORE_REQUESTS_PER_MINUTE") {
+        Some(rate) => rate.parse::<u32>().map_err(|_| anyhow!("'STORE_REQUESTS_PER_MINUTE' is not a number"))?,
+        None => 30,
+    };
+    let respect_robots = match config.get("RESPECT_ROBOTS_TXT") {
+        Some(flag) => flag.parse::<bool>().map_err(|_| anyhow!("'RESPECT_ROBOTS_TXT' must be true or false"))?,
+        None => true,
+    };
+
+    // Headless browsers for the stores configured with `backend = "browser"`
+    let browser_pool_size = match config.get("BROWSER_POOL_SIZE") {
+        Some(size) => size.parse::<usize>().map_err(|_| anyhow!("'BROWSER_POOL_SIZE' is not a number"))?,
+        None => 2,
+    };
+    let browser_page_timeout = match config.get("BROWSER_PAGE_TIMEOUT_SECS") {
+        Some(secs) => Duration::from_secs(secs.parse::<u64>().map_err(|_| anyhow!("'BROWSER_PAGE_TIMEOUT_SECS' is not a number"))?),
+        None => Duration::from_secs(20),
+    };
+    let fetcher = Fetcher::new(
+        http.clone(),
+        BrowserPool::new(browser_pool_size, browser_page_timeout, capture),
+        RateLimiter::new(requests_per_minute),
+        respect_robots,
+    );
+
+    // Optional channel that gets told when a store scraper breaks
+    let admin_channel_id = match config.get("ADMIN_CHANNEL_ID") {
+        Some(id) => Some(serenity::ChannelId(id.parse().map_err(|_| anyhow!("'ADMIN_CHANNEL_ID' is not a valid id"))?)),
+        None => None,
+    };
+    let health_check_interval = match config.get("HEALTH_CHECK_INTERVAL_MINS") {
+        Some(mins) => Duration::from_secs(60 * mins.parse::<u64>().map_err(|_| anyhow!("'HEALTH_CHECK_INTERVAL_MINS' is not a number"))?),
+        None => Duration::from_secs(60 * 60),
+    };
+
+    // Market the stores are searched in and how long their results are reused
+    let region = match config.get("STORE_REGION") {
+        Some(code) => Region::from_code(&code).ok_or_else(|| anyhow!("'STORE_REGION' must be BR or US, got '{}'", code))?,
+        None => Region::Br,
+    };
+    let cache_ttl = match config.get("SEARCH_CACHE_TTL_SECS") {
+        Some(secs) => Duration::from_secs(secs.parse::<u64>().map_err(|_| anyhow!("'SEARCH_CACHE_TTL_SECS' is not a number"))?),
+        None => Duration::from_secs(60),
+    };
+
+    // Transient store failures are retried, stores that keep failing are skipped for a while
+    let retry_attempts = match config.get("STORE_RETRY_ATTEMPTS") {
+        Some(attempts) => attempts.parse::<u32>().map_err(|_| anyhow!("'STORE_RETRY_ATTEMPTS' is not a number"))?,
+        None => 3,
+    };
+    let circuit_threshold = match config.get("CIRCUIT_FAILURE_THRESHOLD") {
+        Some(failures) => failures.parse::<u32>().map_err(|_| anyhow!("'CIRCUIT_FAILURE_THRESHOLD' is not a number"))?,
+        None => 3,
+    };
+    let circuit_cooldown = match config.get("CIRCUIT_COOLDOWN_SECS") {
+        Some(secs) => Duration::from_secs(secs.parse::<u64>().map_err(|_| anyhow!("'CIRCUIT_COOLDOWN_SECS' is not a number"))?),
+        None => Duration::from_secs(5 * 60),
+    };
+
+    // Commands that reach the stores can only be used so often, cache hits are free
+    let cooldown_secs = |name: &str, default: u64| match config.get(name) {
+        Some(secs) => secs.parse::<u64>().map(Duration::from_secs).map_err(|_| anyhow!("'{}' is not a number", name)),
+        None => Ok(Duration::from_secs(default)),
+    };
+    let cooldowns = Cooldowns::new(CooldownConfig {
+        user: cooldown_secs("COOLDOWN_USER_SECS", 15)?,
+        guild: cooldown_secs("COOLDOWN_GUILD_SECS", 5)?,
+        global: cooldown_secs("COOLDOWN_GLOBAL_SECS", 0)?,
+    });
+
+    // Store selectors are validated up front so a typo fails the deploy instead of the scrapers
+    let selectors_path = config.get("SELECTORS_PATH").map(PathBuf::from);
+    let selectors = SelectorConfig::load(selectors_path.as_deref()).map_err(|e| anyhow!("{}", e))?;
+
+    // Guild settings and the other state the bot keeps live in the data directory
+    let data_dir = PathBuf::from(config.get("DATA_DIR").unwrap_or_else(|| "data".to_string()));
+    let settings = SettingsStore::load(data_dir.join("guild_settings.json")).map_err(|e| anyhow!("{}", e))?;
+    let user_settings = SettingsStore::load(data_dir.join("user_settings.json")).map_err(|e| anyhow!("{}", e))?;
+    let catalog = Catalog::load(data_dir.join("catalog.json")).map_err(|e| anyhow!("{}", e))?;
+    let watchlists = Watchlists::load(data_dir.join("watchlists.json")).map_err(|e| anyhow!("{}", e))?;
+    let access = AccessControl::load(data_dir.join("access.json")).map_err(|e| anyhow!("{}", e))?;
+
+    let data = Data(Arc::new(DataInner {
+        ds_token: ds_token.clone(), discord_guild_id, http, fetcher, cache: SearchCache::new(cache_ttl), region,
+        retry: RetryPolicy::new(retry_attempts), breaker: CircuitBreaker::new(circuit_threshold, circuit_cooldown), admin_channel_id, health: Default::default(),
+        selectors: RwLock::new(Arc::new(selectors)), selectors_path, settings, user_settings, catalog, watchlists, access, cooldowns,
+    }));
+
+    // Set gateway intents, which decides what events the bot will be notified about
+    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT | GatewayIntents::DIRECT_MESSAGES;
+
+    // FrameworkOptions contains all of poise's configuration option in one struct
+    // Every option can be omitted to use its default value
+    let options = poise::FrameworkOptions {
+        commands: commands::commands(),
+        prefix_options: poise::PrefixFrameworkOptions {
+            prefix: Some("!ds ".into()),
+            edit_tracker: Some(poise::EditTracker::for_timespan(Duration::from_secs(60 * 5))),
+            ..Default::default()
+        },
+        /// The global error handler for all error cases that may occur
+        on_error: |error| {
+            Box::pin(async move {
+                match error {
+                    poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {:?}", error),
+                    poise::FrameworkError::Command { error, ctx } => {
+                        error!("Error in command `{}`: {:?}", ctx.command().name, error,);
+                    }
+                    poise::FrameworkError::ArgumentParse { error, .. } => {
+                        if let Some(error) = error.downcast_ref::<serenity::RoleParseError>() {
+                            error!("Found a RoleParseError: {:?}", error);
+                        } else {
+                            error!("Not a RoleParseError :(");
+                        }
+                    }
+                    other => {
+                        if let Err(e) = poise::builtins::on_error(other).await {
+                            error!("Error while handling error: {}", e)
+                        }
+                    },
+                }
+            })
+        },
+        /// This code is run before every command
+        pre_command: |ctx| {
+            Box::pin(async move {
+                info!("Executing command {}...", ctx.command().qualified_name);
+            })
+        },
+        /// This code is run after a command if it was successful (returned Ok)
+        post_command: |ctx| {
+            Box::pin(async move {
+                info!("Executed command {}!", ctx.command().qualified_name);
+            })
+        },
+        /// Every command invocation must pass this check to continue execution
+        command_check: Some(|ctx| Box::pin(access::check(ctx))),
+        ..poise::FrameworkOptions::default()
+    };
+
+    let framework = poise::Framework::builder()
+        .options(options)
+        .token(ds_token)
+        .intents(intents)
+        .setup(|ctx, _ready, framework| {
+            Box::pin(async move {
+                println!("Logged in as {}", _ready.user.name);
+                poise::builtins::register_globally(ctx, &framework.options().commands).await?;
+                health::spawn_checks(ctx.clone(), data.clone(), health_check_interval, data.0.admin_channel_id);
+                Ok(data)
+            })
+        });
+
+    Ok(framework)
+}
@@ -1,112 +1,45 @@
-mod commands;
-mod funcs;
-mod structs;
+use deal_scrapper_bot::config::Config;
 
-use anyhow::anyhow;
-use poise::serenity_prelude as serenity;
-use poise::serenity_prelude::GatewayIntents;
+#[cfg(feature = "shuttle")]
+use deal_scrapper_bot::{Data, Error};
+#[cfg(feature = "shuttle")]
 use shuttle_poise::ShuttlePoise;
+#[cfg(feature = "shuttle")]
 use shuttle_secrets::SecretStore;
-use std::{sync::Arc, time::Duration};
-use tracing::{error, info};
-use structs::{Context, Data, DataInner, Error};
 
+/// Runs on Shuttle, with the settings from `Secrets.toml`.
+#[cfg(feature = "shuttle")]
 #[shuttle_runtime::main]
 async fn poise(
     #[shuttle_secrets::Secrets] secret_store: SecretStore,
 ) -> ShuttlePoise<Data, Error> {
-    // Get the discord token set in `Secrets.toml`
-    let ds_token = if let Some(token) = secret_store.get("DISCORD_TOKEN") {
-        token
-    } else {
-        return Err(anyhow!("'DISCORD_TOKEN' was not found").into());
-    };
-
-    let discord_guild_id = if let Some(discord_guild_id) = secret_store.get("DISCORD_GUILD_ID") {
-        discord_guild_id
-    } else {
-        return Err(anyhow!("'DISCORD_GUILD_ID' was not found").into());
-    };
+    let config = Config::new(move |key| secret_store.get(key));
+    let framework = deal_scrapper_bot::framework(config)?
+        .build()
+        .await
+        .map_err(shuttle_runtime::CustomError::new)?;
 
-    let reqwest = reqwest::Client::new();
+    Ok(framework.into())
+}
 
-    let data = Data(Arc::new(DataInner {
-        ds_token: ds_token.clone(), discord_guild_id, reqwest
-    }));
+/// Runs on its own, with the settings from the environment and the file in
+/// `BOT_CONFIG`, `Secrets.toml` when it exists otherwise.
+#[cfg(not(feature = "shuttle"))]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    use std::path::PathBuf;
 
-    // Set gateway intents, which decides what events the bot will be notified about
-    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT | GatewayIntents::DIRECT_MESSAGES;
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
 
-    // FrameworkOptions contains all of poise's configuration option in one struct
-    // Every option can be omitted to use its default value
-    let options = poise::FrameworkOptions {
-        commands: commands::commands(),
-        prefix_options: poise::PrefixFrameworkOptions {
-            prefix: Some("!ds ".into()),
-            edit_tracker: Some(poise::EditTracker::for_timespan(Duration::from_secs(60 * 5))),
-            ..Default::default()
-        },
-        /// The global error handler for all error cases that may occur
-        on_error: |error| {
-            Box::pin(async move {
-                match error {
-                    poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {:?}", error),
-                    poise::FrameworkError::Command { error, ctx } => {
-                        error!("Error in command `{}`: {:?}", ctx.command().name, error,);
-                    }
-                    poise::FrameworkError::ArgumentParse { error, .. } => {
-                        if let Some(error) = error.downcast_ref::<serenity::RoleParseError>() {
-                            error!("Found a RoleParseError: {:?}", error);
-                        } else {
-                            error!("Not a RoleParseError :(");
-                        }
-                    }
-                    other => {
-                        if let Err(e) = poise::builtins::on_error(other).await {
-                            error!("Error while handling error: {}", e)
-                        }
-                    },
-                }
-            })
-        },
-        /// This code is run before every command
-        pre_command: |ctx| {
-            Box::pin(async move {
-                info!("Executing command {}...", ctx.command().qualified_name);
-            })
-        },
-        /// This code is run after a command if it was successful (returned Ok)
-        post_command: |ctx| {
-            Box::pin(async move {
-                info!("Executed command {}!", ctx.command().qualified_name);
-            })
-        },
-        /// Every command invocation must pass this check to continue execution
-        command_check: Some(|ctx| {
-            Box::pin(async move {
-                if ctx.author().id == 123456789 {
-                    return Ok(false);
-                }
-                Ok(true)
-            })
-        }),
-        ..poise::FrameworkOptions::default()
+    let path: Option<PathBuf> = match std::env::var("BOT_CONFIG") {
+        Ok(path) => Some(PathBuf::from(path)),
+        Err(_) => Some(PathBuf::from("Secrets.toml")).filter(|path| path.exists()),
     };
-
-    let framework = poise::Framework::builder()
-        .options(options)
-        .token(ds_token)
-        .intents(intents)
-        .setup(|ctx, _ready, framework| {
-            Box::pin(async move {
-                println!("Logged in as {}", _ready.user.name);
-                poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(data)
-            })
-        })
-        .build()
+    let config = Config::from_env_and_file(path.as_deref())?;
+    deal_scrapper_bot::framework(config)?
+        .run()
         .await
-        .map_err(shuttle_runtime::CustomError::new)?;
-
-    Ok(framework.into())
+        .map_err(|e| anyhow::anyhow!("The bot stopped: {}", e))
 }
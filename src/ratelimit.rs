@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Requests a host may receive in a row before the rate applies.
+const BURST: f64 = 3.0;
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl Bucket {
+    /// Takes a token, or says how long to wait until one is available.
+    fn take(&mut self, now: Instant, per_minute: u32) -> Duration {
+        if let Some(until) = self.paused_until {
+            if until > now {
+                return until - now;
+            }
+            self.paused_until = None;
+        }
+
+        let rate = per_minute.max(1) as f64 / 60.0;
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(BURST);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / rate)
+        }
+    }
+}
+
+/// A token bucket per store host, so timers and busy channels cannot hammer
+/// a store into blocking the bot.
+pub struct RateLimiter {
+    default_per_minute: u32,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(default_per_minute: u32) -> RateLimiter {
+        RateLimiter { default_per_minute, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// Waits until `host` may receive another request.
+    pub async fn acquire(&self, host: &str, per_minute: Option<u32>) {
+        let per_minute = per_minute.unwrap_or(self.default_per_minute);
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let bucket = buckets.entry(host.to_string()).or_insert_with(|| Bucket {
+                    tokens: BURST,
+                    refilled_at: Instant::now(),
+                    paused_until: None,
+                });
+                bucket.take(Instant::now(), per_minute)
+            };
+            if wait.is_zero() {
+                return;
+            }
+            info!("Rate limiting {} for {:?}", host, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Stops sending requests to `host` for a while, as asked by the store.
+    pub fn pause(&self, host: &str, duration: Duration) {
+        info!("Pausing requests to {} for {:?}", host, duration);
+        let until = Instant::now() + duration;
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(host.to_string()).or_insert_with(|| Bucket {
+            tokens: 0.0,
+            refilled_at: Instant::now(),
+            paused_until: None,
+        });
+        bucket.paused_until = Some(bucket.paused_until.map_or(until, |current| current.max(until)));
+    }
+}
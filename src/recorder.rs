@@ -0,0 +1,138 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::structs::Error;
+
+/// How outbound store traffic is handled.
+#[derive(Clone, Debug)]
+pub enum HttpMode {
+    /// Talk to the stores, nothing is written to disk.
+    Live,
+    /// Talk to the stores and save every exchange in the given directory.
+    Record(PathBuf),
+    /// Never touch the network, serve exchanges saved in the given directory.
+    Replay(PathBuf),
+}
+
+impl HttpMode {
+    pub fn from_settings(mode: Option<String>, dir: Option<String>) -> anyhow::Result<HttpMode> {
+        let dir = PathBuf::from(dir.unwrap_or_else(|| "captures".to_string()));
+        match mode.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("live") => Ok(HttpMode::Live),
+            Some("record") => Ok(HttpMode::Record(dir)),
+            Some("replay") => Ok(HttpMode::Replay(dir)),
+            Some(other) => Err(anyhow!("'HTTP_MODE' must be live, record or replay, got '{}'", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// Saves and loads request/response pairs, one JSON file per request.
+///
+/// Files are named after a hash of the method, url and headers, so the same
+/// request always maps to the same capture and replays are deterministic.
+pub struct Recorder {
+    dir: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(dir: PathBuf) -> Recorder {
+        Recorder { dir }
+    }
+
+    fn path_for(&self, request: &RecordedRequest) -> PathBuf {
+        let mut key = format!("{} {}", request.method, request.url);
+        for (name, value) in &request.headers {
+            key.push_str(&format!("\n{}: {}", name, value));
+        }
+        self.dir.join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+
+    pub async fn save(&self, exchange: &Exchange) -> Result<(), Error> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.path_for(&exchange.request);
+        let json = serde_json::to_string_pretty(exchange)?;
+        tokio::fs::write(&path, json).await?;
+
+        info!("Recorded {} {} to {:?}", exchange.request.method, exchange.request.url, path);
+        Ok(())
+    }
+
+    pub async fn load(&self, request: &RecordedRequest) -> Result<RecordedResponse, Error> {
+        let path = self.path_for(request);
+        let json = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => json,
+            Err(_) => return Err(format!("No recorded response for {} {} (expected {:?})", request.method, request.url, path).into()),
+        };
+        let exchange: Exchange = serde_json::from_str(&json)?;
+
+        info!("Replaying {} {} from {:?}", request.method, request.url, path);
+        Ok(exchange.response)
+    }
+}
+
+/// Applies the `HttpMode` around a fetch, whatever backend performs it.
+pub enum Capture {
+    Live,
+    Record(Recorder),
+    Replay(Recorder),
+}
+
+impl Capture {
+    pub fn new(mode: HttpMode) -> Capture {
+        match mode {
+            HttpMode::Live => Capture::Live,
+            HttpMode::Record(dir) => Capture::Record(Recorder::new(dir)),
+            HttpMode::Replay(dir) => Capture::Replay(Recorder::new(dir)),
+        }
+    }
+
+    /// Runs `live` unless replaying, saving what it returned when recording.
+    pub async fn exchange<F>(&self, request: RecordedRequest, live: F) -> Result<RecordedResponse, Error>
+    where
+        F: std::future::Future<Output = Result<RecordedResponse, Error>>,
+    {
+        match self {
+            Capture::Live => live.await,
+            Capture::Replay(recorder) => recorder.load(&request).await,
+            Capture::Record(recorder) => {
+                let response = live.await?;
+                let exchange = Exchange { request, response };
+                recorder.save(&exchange).await?;
+                Ok(exchange.response)
+            }
+        }
+    }
+}
+
+/// FNV-1a, used because it is stable across builds unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -0,0 +1,63 @@
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::http::FetchError;
+use crate::structs::Error;
+
+/// Retries idempotent store fetches with exponential backoff and full jitter.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts: attempts.max(1),
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+
+    pub async fn run<T, F, Fut>(&self, what: &str, mut operation: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.attempts && self.is_retryable(&e) => {
+                    let delay = self.delay(attempt);
+                    warn!("{} failed (attempt {}/{}), retrying in {:?}: {}", what, attempt, self.attempts, delay, e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// A random delay up to the exponential backoff of the attempt.
+    fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(attempt - 1)).min(self.max_delay);
+        rand::thread_rng().gen_range(Duration::ZERO..=backoff)
+    }
+
+    /// Errors worth trying again: network trouble, server errors, and being
+    /// throttled for less time than the longest backoff. A store refusing
+    /// the request outright will refuse it again.
+    fn is_retryable(&self, error: &Error) -> bool {
+        match error.downcast_ref::<FetchError>() {
+            Some(FetchError::Throttled { retry_after, .. }) => retry_after.map_or(true, |wait| wait <= self.max_delay),
+            Some(FetchError::Status { status, .. }) => *status >= 500,
+            Some(FetchError::Disallowed { .. }) => false,
+            None => true,
+        }
+    }
+}
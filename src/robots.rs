@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The product token stores can address in their robots.txt.
+pub const ROBOTS_AGENT: &str = "DealFinderBot";
+
+/// How long a fetched robots.txt is trusted before being fetched again.
+const ROBOTS_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// The Allow/Disallow rules of a robots.txt that apply to the bot.
+#[derive(Debug, Default)]
+pub struct Robots {
+    rules: Vec<(bool, String)>,
+}
+
+impl Robots {
+    /// Parses a robots.txt, keeping the group addressed to the bot when there
+    /// is one and the `*` group otherwise.
+    pub fn parse(text: &str) -> Robots {
+        let agent = ROBOTS_AGENT.to_lowercase();
+        let mut own: Vec<(bool, String)> = Vec::new();
+        let mut any: Vec<(bool, String)> = Vec::new();
+        let mut has_own = false;
+
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else { continue };
+            let field = field.trim().to_lowercase();
+            let value = value.trim();
+
+            match field.as_str() {
+                "user-agent" => {
+                    // A user-agent line after rules starts a new group
+                    if in_rules {
+                        group_agents.clear();
+                        in_rules = false;
+                    }
+                    group_agents.push(value.to_lowercase());
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // An empty Disallow allows everything
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (field == "allow", value.to_string());
+                    if group_agents.iter().any(|a| agent.contains(a.as_str()) && a != "*") {
+                        has_own = true;
+                        own.push(rule.clone());
+                    }
+                    if group_agents.iter().any(|a| a == "*") {
+                        any.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Robots { rules: if has_own { own } else { any } }
+    }
+
+    /// Whether `path` (with its query) may be fetched, the longest matching
+    /// rule wins and Allow wins ties.
+    pub fn allows(&self, path: &str) -> bool {
+        let mut best: Option<(usize, bool)> = None;
+        for (allow, pattern) in &self.rules {
+            if matches(pattern, path) {
+                let len = pattern.len();
+                best = match best {
+                    Some((best_len, best_allow)) if best_len > len || (best_len == len && best_allow) => best,
+                    _ => Some((len, *allow)),
+                };
+            }
+        }
+        best.map(|(_, allow)| allow).unwrap_or(true)
+    }
+}
+
+/// Matches a robots.txt path pattern, supporting `*` and a trailing `$`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    let mut rest = path;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 && anchored {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    !anchored || rest.is_empty() || pattern.ends_with('*')
+}
+
+/// The robots.txt of every store host, fetched once a day.
+#[derive(Default)]
+pub struct RobotsCache {
+    hosts: Mutex<HashMap<String, (Instant, Arc<Robots>)>>,
+}
+
+impl RobotsCache {
+    pub fn get(&self, origin: &str) -> Option<Arc<Robots>> {
+        let hosts = self.hosts.lock().unwrap();
+        match hosts.get(origin) {
+            Some((fetched_at, robots)) if fetched_at.elapsed() < ROBOTS_TTL => Some(robots.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&self, origin: &str, robots: Robots) -> Arc<Robots> {
+        let robots = Arc::new(robots);
+        self.hosts.lock().unwrap().insert(origin.to_string(), (Instant::now(), robots.clone()));
+        robots
+    }
+}
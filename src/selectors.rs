@@ -0,0 +1,216 @@
+use scraper::Selector;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use crate::fetcher::Backend;
+use crate::structs::Error;
+
+/// The file bundled with the bot, used when no other file is configured.
+const DEFAULT_SELECTORS: &str = include_str!("../selectors.toml");
+
+/// The store sections the scrapers expect and the selectors each of them
+/// cannot work without.
+const STORES: [(&str, &[&str]); 19] = [
+    ("steam", &["full_price", "discounted_price", "discount", "image", "platform_windows", "platform_macos", "platform_linux"]),
+    ("epic", &["full_price", "discounted_price", "discount", "image"]),
+    ("nuuvem", &["currency", "price_integer", "price_decimal", "discount", "image"]),
+    ("gog", &["full_price", "discounted_price", "discount", "image"]),
+    ("gmg", &["full_price", "discounted_price", "discount", "image"]),
+    ("fanatical", &["full_price", "discounted_price", "discount", "image", "link"]),
+    ("playstation", &["full_price", "discounted_price", "discount", "image", "link"]),
+    ("ubisoft", &["full_price", "discounted_price", "discount", "image", "link"]),
+    ("ea", &["full_price", "discounted_price", "discount", "image", "link"]),
+    ("battlenet", &["full_price", "discounted_price", "discount", "image", "link"]),
+    ("kabum", &["full_price", "discounted_price", "discount", "image", "link"]),
+    ("amazon_br", &["full_price", "discounted_price", "image", "link"]),
+    // Rows without a price are free games
+    ("itch", &["discounted_price", "discount", "image", "link"]),
+    // Read from an itch.io game page, whose single row holds the buy button
+    ("itch_game", &["discounted_price", "ends"]),
+    // Key marketplaces list the lowest offer, the offers with their seller
+    // are read from the product page
+    ("eneba", &["discounted_price", "image", "link"]),
+    ("eneba_offers", &["discounted_price", "seller", "seller_rating"]),
+    ("kinguin", &["discounted_price", "image", "link"]),
+    ("kinguin_offers", &["discounted_price", "seller", "seller_rating"]),
+    // Read from a Fanatical product page, one row per bundle with the game
+    ("fanatical_bundles", &["discounted_price", "ends"]),
+];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSelectors {
+    results: String,
+    row: String,
+    title: String,
+    full_price: Option<String>,
+    discounted_price: Option<String>,
+    discount: Option<String>,
+    currency: Option<String>,
+    price_integer: Option<String>,
+    price_decimal: Option<String>,
+    image: Option<String>,
+    #[serde(default = "default_image_attr")]
+    image_attr: String,
+    link: Option<String>,
+    link_attr: Option<String>,
+    ends: Option<String>,
+    ends_attr: Option<String>,
+    seller: Option<String>,
+    seller_rating: Option<String>,
+    platform_windows: Option<String>,
+    platform_macos: Option<String>,
+    platform_linux: Option<String>,
+    #[serde(default)]
+    backend: Backend,
+    wait_for: Option<String>,
+    page_timeout_secs: Option<u64>,
+    requests_per_minute: Option<u32>,
+}
+
+fn default_image_attr() -> String {
+    "src".to_string()
+}
+
+/// The compiled selectors of a single store.
+#[derive(Debug)]
+pub struct StoreSelectors {
+    pub results: Selector,
+    pub row: Selector,
+    pub title: Selector,
+    pub full_price: Option<Selector>,
+    pub discounted_price: Option<Selector>,
+    pub discount: Option<Selector>,
+    pub currency: Option<Selector>,
+    pub price_integer: Option<Selector>,
+    pub price_decimal: Option<Selector>,
+    pub image: Option<Selector>,
+    pub image_attr: String,
+    pub link: Option<Selector>,
+    pub link_attr: Option<String>,
+    /// When a sale or bundle ends, read from `ends_attr` or the element text.
+    pub ends: Option<Selector>,
+    pub ends_attr: Option<String>,
+    /// Who sells an offer on a key marketplace, and how buyers rated them.
+    pub seller: Option<Selector>,
+    pub seller_rating: Option<Selector>,
+    /// The icons a row shows for each system the game runs on.
+    pub platform_windows: Option<Selector>,
+    pub platform_macos: Option<Selector>,
+    pub platform_linux: Option<Selector>,
+    pub backend: Backend,
+    /// Selector a browser fetch waits for, the results container by default.
+    pub wait_for: String,
+    pub page_timeout: Option<Duration>,
+    /// Overrides the default request rate for the store's host.
+    pub requests_per_minute: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct SelectorConfig {
+    pub source: String,
+    stores: HashMap<String, Arc<StoreSelectors>>,
+}
+
+impl SelectorConfig {
+    /// Loads the selectors from `path`, falling back to the bundled file
+    /// when no path is given.
+    pub fn load(path: Option<&Path>) -> Result<SelectorConfig, Error> {
+        match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read selectors file {:?}: {}", path, e))?;
+                SelectorConfig::parse(&text, &path.display().to_string())
+            }
+            None => SelectorConfig::parse(DEFAULT_SELECTORS, "bundled selectors.toml"),
+        }
+    }
+
+    /// Parses and validates every store section, reporting all the broken
+    /// selectors at once rather than only the first one.
+    pub fn parse(text: &str, source: &str) -> Result<SelectorConfig, Error> {
+        let raw: HashMap<String, RawSelectors> = toml::from_str(text)
+            .map_err(|e| format!("{}: {}", source, e))?;
+
+        let mut problems: Vec<String> = Vec::new();
+        for key in raw.keys() {
+            if !STORES.iter().any(|(store, _)| *store == key.as_str()) {
+                problems.push(format!("[{}] is not a known store", key));
+            }
+        }
+
+        let mut stores: HashMap<String, Arc<StoreSelectors>> = HashMap::new();
+        for (key, required) in STORES {
+            let Some(section) = raw.get(key) else {
+                problems.push(format!("[{}] section is missing", key));
+                continue;
+            };
+            let mut compile = |field: &str, selector: &Option<String>| -> Option<Selector> {
+                match selector {
+                    Some(selector) => match Selector::parse(selector) {
+                        Ok(s) => Some(s),
+                        Err(e) => {
+                            problems.push(format!("[{}].{}: invalid selector `{}`: {}", key, field, selector, e));
+                            None
+                        }
+                    },
+                    None => {
+                        if required.contains(&field) {
+                            problems.push(format!("[{}].{} is required", key, field));
+                        }
+                        None
+                    }
+                }
+            };
+
+            let results = compile("results", &Some(section.results.clone()));
+            let row = compile("row", &Some(section.row.clone()));
+            let title = compile("title", &Some(section.title.clone()));
+            let wait_for = section.wait_for.clone().unwrap_or_else(|| section.results.clone());
+            compile("wait_for", &Some(wait_for.clone()));
+            let selectors = StoreSelectors {
+                full_price: compile("full_price", &section.full_price),
+                discounted_price: compile("discounted_price", &section.discounted_price),
+                discount: compile("discount", &section.discount),
+                currency: compile("currency", &section.currency),
+                price_integer: compile("price_integer", &section.price_integer),
+                price_decimal: compile("price_decimal", &section.price_decimal),
+                image: compile("image", &section.image),
+                image_attr: section.image_attr.clone(),
+                link: compile("link", &section.link),
+                link_attr: section.link_attr.clone(),
+                ends: compile("ends", &section.ends),
+                ends_attr: section.ends_attr.clone(),
+                seller: compile("seller", &section.seller),
+                seller_rating: compile("seller_rating", &section.seller_rating),
+                platform_windows: compile("platform_windows", &section.platform_windows),
+                platform_macos: compile("platform_macos", &section.platform_macos),
+                platform_linux: compile("platform_linux", &section.platform_linux),
+                backend: section.backend,
+                wait_for,
+                page_timeout: section.page_timeout_secs.map(Duration::from_secs),
+                requests_per_minute: section.requests_per_minute,
+                results: match results { Some(s) => s, None => continue },
+                row: match row { Some(s) => s, None => continue },
+                title: match title { Some(s) => s, None => continue },
+            };
+            stores.insert(key.to_string(), Arc::new(selectors));
+        }
+
+        if !problems.is_empty() {
+            return Err(format!("{} has {} problem(s):\n{}", source, problems.len(), problems.join("\n")).into());
+        }
+
+        info!("Loaded selectors for {} stores from {}", stores.len(), source);
+        Ok(SelectorConfig { source: source.to_string(), stores })
+    }
+
+    /// The selectors of a store, every known store is present once validated.
+    pub fn store(&self, key: &str) -> Arc<StoreSelectors> {
+        self.stores[key].clone()
+    }
+}
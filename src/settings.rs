@@ -0,0 +1,75 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::i18n::Lang;
+use crate::json_file::JsonFile;
+use crate::structs::{Error, Region, Store};
+
+/// What a guild changed from the bot defaults.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    /// The market prices are searched in, the bot's own when unset.
+    pub region: Option<Region>,
+    /// The language of members whose Discord language has no catalog.
+    pub language: Option<Lang>,
+    /// Keys of the stores the guild turned off.
+    pub disabled_stores: BTreeSet<String>,
+    /// Where `deal` shares the deals found at the minimum discount or more.
+    pub announcement_channel: Option<u64>,
+    /// The discount, in percent, a deal needs to be announced.
+    pub min_discount: u8,
+    /// Show key reseller marketplaces in `deal`, off unless a guild opts in.
+    pub resellers: bool,
+}
+
+impl GuildSettings {
+    /// Whether the guild searches the store, resellers also need the opt-in.
+    pub fn searches(&self, store: Store) -> bool {
+        !self.disabled_stores.contains(store.key()) && (self.resellers || !store.is_reseller())
+    }
+}
+
+/// What a user changed from the bot defaults.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// Replies in this language whatever the Discord language is.
+    pub language: Option<Lang>,
+}
+
+/// The settings of every guild or user, written to a JSON file on each
+/// change so they survive restarts.
+pub struct SettingsStore<T> {
+    entries: JsonFile<HashMap<u64, T>>,
+}
+
+impl<T: Clone + Default + Serialize + DeserializeOwned> SettingsStore<T> {
+    /// Reads the settings file, a missing file means nobody changed anything yet.
+    pub fn load(path: PathBuf) -> Result<SettingsStore<T>, Error> {
+        let entries: JsonFile<HashMap<u64, T>> = JsonFile::load(path)?;
+        info!("Loaded {} settings from {:?}", entries.read(HashMap::len), entries.path());
+        Ok(SettingsStore { entries })
+    }
+
+    /// The settings of a guild or user, direct messages get the guild defaults.
+    pub fn get(&self, id: Option<impl Into<u64>>) -> T {
+        id.and_then(|id| self.entries.read(|entries| entries.get(&id.into()).cloned()))
+            .unwrap_or_default()
+    }
+
+    /// Whether the settings of any guild or user pass the test.
+    pub fn any(&self, test: impl Fn(&T) -> bool) -> bool {
+        self.entries.read(|entries| entries.values().any(test))
+    }
+
+    pub async fn update(&self, id: impl Into<u64>, change: impl FnOnce(&mut T)) -> Result<T, Error> {
+        self.entries.update(|entries| {
+            let settings = entries.entry(id.into()).or_default();
+            change(settings);
+            settings.clone()
+        }).await
+    }
+}
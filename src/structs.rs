@@ -1,4 +1,21 @@
-use std::{fmt::Display, sync::Arc};
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude as serenity;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Display, path::PathBuf, sync::{Arc, Mutex, RwLock}};
+
+use crate::access::AccessControl;
+use crate::health::HealthReport;
+use crate::cache::SearchCache;
+use crate::catalog::Catalog;
+use crate::circuit::CircuitBreaker;
+use crate::cooldown::Cooldowns;
+use crate::fetcher::Fetcher;
+use crate::http::HttpClient;
+use crate::i18n::{tr, tr_args, Lang};
+use crate::retry::RetryPolicy;
+use crate::selectors::SelectorConfig;
+use crate::settings::{GuildSettings, SettingsStore, UserSettings};
+use crate::watchlist::Watchlists;
 
 #[derive(Clone)]
 pub struct Data(pub Arc<DataInner>);
@@ -6,18 +23,392 @@ pub struct Data(pub Arc<DataInner>);
 pub struct DataInner {
     pub discord_guild_id: String,
     pub ds_token: String,
-    pub reqwest: reqwest::Client,
+    pub http: HttpClient,
+    pub fetcher: Fetcher,
+    pub cache: SearchCache,
+    pub retry: RetryPolicy,
+    pub breaker: CircuitBreaker,
+    pub region: Region,
+    pub admin_channel_id: Option<serenity::ChannelId>,
+    pub health: Mutex<HashMap<Store, HealthReport>>,
+    pub selectors: RwLock<Arc<SelectorConfig>>,
+    pub selectors_path: Option<PathBuf>,
+    pub settings: SettingsStore<GuildSettings>,
+    pub user_settings: SettingsStore<UserSettings>,
+    pub catalog: Catalog,
+    pub watchlists: Watchlists,
+    pub access: AccessControl,
+    pub cooldowns: Cooldowns,
 }
 
-#[derive(Clone, Debug)]
+impl Data {
+    /// The selectors currently in use, a reload swaps them for later calls.
+    pub fn selectors(&self) -> Arc<SelectorConfig> {
+        self.0.selectors.read().unwrap().clone()
+    }
+
+    /// The market a guild searches in, the bot's own unless the guild picked one.
+    pub fn region(&self, guild_id: Option<serenity::GuildId>) -> Region {
+        self.0.settings.get(guild_id).region.unwrap_or(self.0.region)
+    }
+}
+
+/// The storefronts the bot knows how to search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Store {
+    Steam,
+    Epic,
+    Nuuvem,
+    Gog,
+    Gmg,
+    Humble,
+    Fanatical,
+    Microsoft,
+    PlayStation,
+    Nintendo,
+    Itch,
+    Ubisoft,
+    Ea,
+    BattleNet,
+    Kabum,
+    AmazonBr,
+    Eneba,
+    Kinguin,
+}
+
+impl Store {
+    pub const ALL: [Store; 18] = [
+        Store::Steam, Store::Epic, Store::Nuuvem, Store::Gog, Store::Gmg, Store::Humble, Store::Fanatical,
+        Store::Microsoft, Store::PlayStation, Store::Nintendo, Store::Itch, Store::Ubisoft, Store::Ea,
+        Store::BattleNet, Store::Kabum, Store::AmazonBr, Store::Eneba, Store::Kinguin,
+    ];
+
+    pub fn from_key(key: &str) -> Option<Store> {
+        Store::ALL.into_iter().find(|store| store.key() == key)
+    }
+
+    /// Short identifier of the store, also its section in `selectors.toml`
+    /// for the stores that are scraped with selectors.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Store::Steam => "steam",
+            Store::Epic => "epic",
+            Store::Nuuvem => "nuuvem",
+            Store::Gog => "gog",
+            Store::Gmg => "gmg",
+            Store::Humble => "humble",
+            Store::Fanatical => "fanatical",
+            Store::Microsoft => "microsoft",
+            Store::PlayStation => "playstation",
+            Store::Nintendo => "nintendo",
+            Store::Itch => "itch",
+            Store::Ubisoft => "ubisoft",
+            Store::Ea => "ea",
+            Store::BattleNet => "battlenet",
+            Store::Kabum => "kabum",
+            Store::AmazonBr => "amazon_br",
+            Store::Eneba => "eneba",
+            Store::Kinguin => "kinguin",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Store::Steam => "Steam",
+            Store::Epic => "Epic Games",
+            Store::Nuuvem => "Nuuvem",
+            Store::Gog => "GOG",
+            Store::Gmg => "Green Man Gaming",
+            Store::Humble => "Humble Store",
+            Store::Fanatical => "Fanatical",
+            Store::Microsoft => "Microsoft Store",
+            Store::PlayStation => "PlayStation Store",
+            Store::Nintendo => "Nintendo eShop",
+            Store::Itch => "itch.io",
+            Store::Ubisoft => "Ubisoft Store",
+            Store::Ea => "EA app",
+            Store::BattleNet => "Battle.net",
+            Store::Kabum => "KaBuM!",
+            Store::AmazonBr => "Amazon Brasil",
+            Store::Eneba => "Eneba",
+            Store::Kinguin => "Kinguin",
+        }
+    }
+
+    /// A title that should always be listed, used to tell a broken scraper
+    /// apart from a search that simply has no results.
+    pub fn canary(&self) -> &'static str {
+        match self {
+            Store::Steam => "Portal 2",
+            Store::Epic => "Alan Wake 2",
+            Store::Nuuvem => "Hollow Knight",
+            Store::Gog => "The Witcher 3: Wild Hunt",
+            Store::Gmg => "Cyberpunk 2077",
+            Store::Humble => "Stardew Valley",
+            Store::Fanatical => "Hades",
+            Store::Microsoft => "Forza Horizon 5",
+            Store::PlayStation => "Stray",
+            Store::Nintendo => "Hollow Knight",
+            Store::Itch => "Celeste",
+            Store::Ubisoft => "Far Cry 6",
+            Store::Ea => "Battlefield 2042",
+            Store::BattleNet => "Diablo IV",
+            Store::Kabum => "Cyberpunk 2077",
+            Store::AmazonBr => "Minecraft",
+            Store::Eneba => "Cyberpunk 2077",
+            Store::Kinguin => "Cyberpunk 2077",
+        }
+    }
+
+    /// Key marketplaces resell keys from third parties, guilds have to opt in
+    /// to see them and they are kept apart from the official stores.
+    pub fn is_reseller(&self) -> bool {
+        matches!(self, Store::Eneba | Store::Kinguin)
+    }
+
+    /// The DRM of everything the store sells, stores selling keys for
+    /// several launchers have none.
+    pub fn drm(&self) -> Option<&'static str> {
+        match self {
+            Store::Steam => Some("Steam"),
+            Store::Epic => Some("Epic Games Launcher"),
+            Store::Gog => Some("DRM-free (GOG Galaxy optional)"),
+            Store::Microsoft => Some("Microsoft Store"),
+            Store::PlayStation => Some("PlayStation Network"),
+            Store::Nintendo => Some("Nintendo Account"),
+            Store::Itch => Some("DRM-free (itch.io)"),
+            Store::Ubisoft => Some("Ubisoft Connect"),
+            Store::Ea => Some("EA app"),
+            Store::BattleNet => Some("Battle.net"),
+            _ => None,
+        }
+    }
+
+    /// Local retailers only sell in their own market.
+    pub fn sells_in(&self, region: Region) -> bool {
+        match self {
+            Store::Nuuvem | Store::Kabum | Store::AmazonBr => region == Region::Br,
+            _ => true,
+        }
+    }
+
+    /// Publisher stores only sell their own games, so `deal` leaves them out
+    /// rather than listing them as not found for everything else.
+    pub fn is_publisher(&self) -> bool {
+        matches!(self, Store::Ubisoft | Store::Ea | Store::BattleNet)
+    }
+
+    /// The platform the store sells games for.
+    pub fn platform(&self) -> Platform {
+        match self {
+            Store::PlayStation => Platform::PlayStation,
+            Store::Nintendo => Platform::Switch,
+            _ => Platform::Pc,
+        }
+    }
+}
+
+impl Display for Store {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Pc,
+    PlayStation,
+    Switch,
+}
+
+impl Platform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Pc => "PC",
+            Platform::PlayStation => "PlayStation",
+            Platform::Switch => "Nintendo Switch",
+        }
+    }
+}
+
+/// Which stores `deal` compares.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum PlatformFilter {
+    #[default]
+    #[name = "PC"]
+    Pc,
+    #[name = "PlayStation"]
+    PlayStation,
+    #[name = "Switch"]
+    Switch,
+    #[name = "All"]
+    All,
+}
+
+impl PlatformFilter {
+    pub fn includes(&self, store: Store) -> bool {
+        match self {
+            PlatformFilter::Pc => store.platform() == Platform::Pc,
+            PlatformFilter::PlayStation => store.platform() == Platform::PlayStation,
+            PlatformFilter::Switch => store.platform() == Platform::Switch,
+            PlatformFilter::All => true,
+        }
+    }
+}
+
+/// The market prices are searched in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Region {
+    Br,
+    Us,
+}
+
+impl Region {
+    pub const ALL: [Region; 2] = [Region::Br, Region::Us];
+
+    pub fn from_code(code: &str) -> Option<Region> {
+        match code.to_uppercase().as_str() {
+            "BR" => Some(Region::Br),
+            "US" => Some(Region::Us),
+            _ => None,
+        }
+    }
+
+    pub fn country(&self) -> &'static str {
+        match self {
+            Region::Br => "BR",
+            Region::Us => "US",
+        }
+    }
+
+    pub fn currency(&self) -> &'static str {
+        match self {
+            Region::Br => "BRL",
+            Region::Us => "USD",
+        }
+    }
+
+    pub fn locale(&self) -> &'static str {
+        match self {
+            Region::Br => "pt-BR",
+            Region::Us => "en-US",
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.country())
+    }
+}
+
+/// A store bundle that includes the game.
+#[derive(Clone, Debug, Default)]
+pub struct Bundle {
+    pub name: String,
+    /// The price of the cheapest tier with the game.
+    pub price: String,
+    pub ends: Option<DateTime<Utc>>,
+    pub url: String,
+}
+
+impl Bundle {
+    pub fn text(&self, lang: Lang) -> String {
+        let mut text = tr_args(lang, "price.bundle", &[("name", &self.name), ("url", &self.url), ("price", &self.price)]);
+        if let Some(ends) = self.ends {
+            text.push_str(&tr_args(lang, "price.bundle_ends", &[("when", &format!("<t:{}:R>", ends.timestamp()))]));
+        }
+        text
+    }
+}
+
+/// What else the store tells about the offer, shown under its price.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Note {
+    HumbleChoice,
+    HumbleBundle,
+    GamePass,
+    /// A "name your own price" game, with its minimum as listed.
+    NameYourPrice(String),
+    NameYourPriceFree,
+    Free,
+}
+
+impl Note {
+    pub fn text(&self, lang: Lang) -> String {
+        match self {
+            Note::HumbleChoice => tr(lang, "note.humble_choice"),
+            Note::HumbleBundle => tr(lang, "note.humble_bundle"),
+            Note::GamePass => tr(lang, "note.game_pass"),
+            Note::NameYourPrice(minimum) => tr_args(lang, "note.name_your_price", &[("minimum", minimum)]),
+            Note::NameYourPriceFree => tr(lang, "note.name_your_price_free"),
+            Note::Free => tr(lang, "note.free"),
+        }
+    }
+}
+
+/// The lowest offer of a key marketplace, whose keys come from third-party sellers.
+#[derive(Clone, Debug, Default)]
+pub struct ResellerOffer {
+    pub seller: String,
+    pub seller_rating: String,
+    /// The region the key activates in, as listed.
+    pub region_lock: String,
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct Game {
     pub site: String,
+    /// The store's own id of the product, when its search tells it.
+    pub product_id: String,
     pub name: String,
     pub currency: String,
     pub full_price: String,
     pub discounted_price: String,
     pub discount: String,
     pub img_url: String,
+    pub url: String,
+    /// Extra lines shown under the price, such as bundle or subscription inclusion.
+    pub notes: Vec<Note>,
+    /// When the current discount ends, for stores that tell.
+    pub sale_end: Option<DateTime<Utc>>,
+    /// Empty for the standard edition.
+    pub edition: String,
+    /// Where the game is activated or what protects it.
+    pub drm: String,
+    pub platforms: Vec<String>,
+    /// Active bundles containing the game, for stores that list them.
+    pub bundles: Vec<Bundle>,
+    /// Set for key marketplaces.
+    pub offer: Option<ResellerOffer>,
+}
+
+impl Game {
+    /// Identifies the product within its store, its page when the store has no
+    /// id for it, without the tracking query or fragment that vary per search.
+    pub fn product_key(&self) -> &str {
+        if self.product_id.is_empty() {
+            self.url.split(['?', '#']).next().unwrap_or_default()
+        } else {
+            &self.product_id
+        }
+    }
+
+    /// What the game costs now, free when the store lists no price.
+    pub fn price(&self, lang: Lang) -> String {
+        let price = if self.discounted_price.is_empty() { &self.full_price } else { &self.discounted_price };
+        if price.is_empty() { tr(lang, "price.free") } else { price.to_string() }
+    }
+}
+
+/// What a store search returned, along with how much of the page matched
+/// the selectors so structural breakage can be told apart from no results.
+#[derive(Clone, Debug, Default)]
+pub struct Scrape {
+    pub games: Vec<Game>,
+    pub containers: usize,
+    pub rows: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -30,31 +421,28 @@ pub struct GameOpt {
     pub game: Option<Game>,
 }
 
-impl Display for GameOpt {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl GameOpt {
+    /// The price lines of a store in a comparison.
+    pub fn text(&self, lang: Lang) -> String {
         match self.game.as_ref() {
             Some(g) => {
-                if g.discounted_price.is_empty() {
-                    if g.site == "Nuuvem" {
-                        write!(f, "Price: {}{}\nDiscount: {}", g.currency, g.full_price, g.discount)
-                    } else {
-                        write!(f, "Price: {}\nDiscount: {}", g.full_price, g.discount)
-                    }
-                } else {
-                    if g.site == "Nuuvem" {
-                        write!(f, "Price: {}{}\nDiscount: {}", g.currency, g.discounted_price, g.discount)
-                    } else {
-                        write!(f, "Price: {}\nDiscount: {}", g.discounted_price, g.discount)
-                    }
+                let price = if g.site == "Nuuvem" { format!("{}{}", g.currency, g.price(lang)) } else { g.price(lang) };
+                let mut text = format!("{}\n{}", tr_args(lang, "price.price", &[("price", &price)]), tr_args(lang, "price.discount", &[("discount", &g.discount)]));
+                if let Some(sale_end) = g.sale_end {
+                    text.push('\n');
+                    text.push_str(&tr_args(lang, "price.sale_ends", &[("when", &format!("<t:{}:R>", sale_end.timestamp()))]));
                 }
+                for note in &g.notes {
+                    text.push('\n');
+                    text.push_str(&note.text(lang));
+                }
+                text
             }
-            None => write!(f, "Not found!"),
+            None => tr(lang, "price.not_found"),
         }
     }
 }
 
-
-
 pub type Command = poise::Command<Data, CommandError>;
 pub type CommandError = Error;
 pub type CommandResult<E=Error> = Result<(), E>;
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use poise::serenity_prelude as serenity;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::json_file::JsonFile;
+use crate::structs::Error;
+
+/// A game a user follows, by its catalog id.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchEntry {
+    pub game_id: u64,
+    pub added_at: DateTime<Utc>,
+}
+
+/// The watchlist of every user, written to a JSON file on each change.
+pub struct Watchlists {
+    users: JsonFile<HashMap<u64, Vec<WatchEntry>>>,
+}
+
+impl Watchlists {
+    /// Reads the watchlists file, a missing file means nobody watches anything yet.
+    pub fn load(path: PathBuf) -> Result<Watchlists, Error> {
+        let users: JsonFile<HashMap<u64, Vec<WatchEntry>>> = JsonFile::load(path)?;
+        info!("Loaded the watchlists of {} users from {:?}", users.read(HashMap::len), users.path());
+        Ok(Watchlists { users })
+    }
+
+    pub fn list(&self, user_id: serenity::UserId) -> Vec<WatchEntry> {
+        self.users.read(|users| users.get(&user_id.0).cloned().unwrap_or_default())
+    }
+
+    /// Adds the games the user does not watch yet and returns them.
+    pub async fn add(&self, user_id: serenity::UserId, game_ids: &[u64]) -> Result<Vec<u64>, Error> {
+        self.change(user_id, |entries| {
+            let mut added: Vec<u64> = Vec::new();
+            for game_id in game_ids {
+                if !entries.iter().any(|entry| entry.game_id == *game_id) {
+                    entries.push(WatchEntry { game_id: *game_id, added_at: Utc::now() });
+                    added.push(*game_id);
+                }
+            }
+            added
+        }).await
+    }
+
+    /// Whether the game was watched.
+    pub async fn remove(&self, user_id: serenity::UserId, game_id: u64) -> Result<bool, Error> {
+        self.change(user_id, |entries| {
+            let before = entries.len();
+            entries.retain(|entry| entry.game_id != game_id);
+            entries.len() != before
+        }).await
+    }
+
+    async fn change<T>(&self, user_id: serenity::UserId, change: impl FnOnce(&mut Vec<WatchEntry>) -> T) -> Result<T, Error> {
+        self.users.update(|users| {
+            let result = change(users.entry(user_id.0).or_default());
+            users.retain(|_, entries| !entries.is_empty());
+            result
+        }).await
+    }
+}